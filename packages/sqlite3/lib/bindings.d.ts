@@ -0,0 +1,1162 @@
+/* tslint:disable */
+/* eslint-disable */
+
+/* auto-generated by NAPI-RS */
+
+/** Definition of a user-defined aggregate function. */
+export interface AggregateOptions {
+  /**
+   * The initial value of the accumulator, or a function returning it for each group.
+   * @type {unknown} [start=null]
+   */
+  start?: unknown
+  /**
+   * Adds a row to the accumulator, called with the accumulator followed by the SQL arguments.
+   * Its return value replaces the accumulator, unless it is `undefined`.
+   * @type {Function} step
+   */
+  step: (...args: any[]) => any
+  /**
+   * Removes a row from the accumulator, with the same signature as `step`.
+   * Supplying it makes the aggregate usable as a window function.
+   * @type {Function} [inverse]
+   */
+  inverse?: (...args: any[]) => any
+  /**
+   * Computes the result from the accumulator, which is returned as is when missing.
+   * @type {Function} [result]
+   */
+  result?: (...args: any[]) => any
+  /** @type {boolean} [deterministic=false] */
+  deterministic?: boolean
+  /**
+   * Whether the function accepts any number of arguments instead of `step.length - 1`.
+   * @type {boolean} [varargs=false]
+   */
+  varargs?: boolean
+  /** @type {boolean} [directOnly=false] */
+  directOnly?: boolean
+}
+/** Options for `Database.backup()`. */
+export interface BackupOptions {
+  /**
+   * Number of pages copied on each step, `0` or less copies the whole database in a single step.
+   * @type {number} [pages=100]
+   */
+  pages?: number
+  /**
+   * Called after each step with the progress of the backup.
+   * @type {Function} [progress]
+   */
+  progress?: (progress: BackupProgress) => void
+}
+/** Progress of a backup, given to the `progress` callback. */
+export interface BackupProgress {
+  /** @type {number} totalPages - Number of pages in the source database. */
+  totalPages: number
+  /** @type {number} remainingPages - Number of pages left to copy. */
+  remainingPages: number
+}
+/** Options for `Database.openBlob()`. */
+export interface BlobOptions {
+  /**
+   * Whether to open the BLOB for reading only.
+   * @type {boolean} [readonly=false]
+   */
+  readonly?: boolean
+  /**
+   * Name of the database holding the table, such as `temp` or an attached one.
+   * @type {string} [database='main']
+   */
+  database?: string
+}
+/** Options for `Database.importJson()`. */
+export interface ImportOptions {
+  /**
+   * Fields to import, each into the column of the same name, or an object mapping the
+   * name of each field to import to the column receiving it. By default, every field of
+   * the first row is imported into the column of the same name.
+   * @type {string[] | Record<string, string>} [columns]
+   */
+  columns?: Columns
+  /**
+   * Types the values of some columns are converted to before they are inserted, failing
+   * on values that cannot be. `boolean` accepts `true`, `false`, `yes`, `no`, `1` and `0`.
+   * @type {Record<string, string>} [types]
+   */
+  types?: Record<string, 'text' | 'integer' | 'real' | 'boolean'>
+}
+/** Options for `Database.importCsv()`. */
+export interface CsvImportOptions {
+  /**
+   * Fields to import, each into the column of the same name, or an object mapping the
+   * name of each field to import to the column receiving it. Files without a header line
+   * need an array, which names their fields in order.
+   * @type {string[] | Record<string, string>} [columns]
+   */
+  columns?: Columns
+  /**
+   * Types the values of some columns are converted to, see `ImportOptions`. Other values
+   * are inserted as text, which columns declared as `INTEGER` or `REAL` still convert.
+   * @type {Record<string, string>} [types]
+   */
+  types?: Record<string, 'text' | 'integer' | 'real' | 'boolean'>
+  /** @type {string} [delimiter=','] */
+  delimiter?: string
+  /**
+   * Whether the first line holds the names of the fields.
+   * @type {boolean} [header=true]
+   */
+  header?: boolean
+  /**
+   * Text read as NULL, such as `NULL` or an empty string.
+   * @type {string} [nullValue]
+   */
+  nullValue?: string
+}
+/** Options for `Database.exportCsv()`. */
+export interface CsvExportOptions {
+  /** @type {string} [delimiter=','] */
+  delimiter?: string
+  /**
+   * Whether to write the names of the columns on the first line.
+   * @type {boolean} [header=true]
+   */
+  header?: boolean
+  /**
+   * Text written for NULL values.
+   * @type {string} [nullValue='']
+   */
+  nullValue?: string
+}
+/** Options for `Database.busyHandler()`. */
+export interface BusyHandlerOptions {
+  /**
+   * Retries before failing with `SQLITE_BUSY`. Unlimited when a handler is given,
+   * which then decides when to stop.
+   * @type {number} [retries=10]
+   */
+  retries?: number
+  /**
+   * Milliseconds to wait before the first retry, doubled before each following one.
+   * @type {number} [delay=10]
+   */
+  delay?: number
+  /**
+   * Upper bound of the wait between two retries, in milliseconds.
+   * @type {number} [maxDelay=1000]
+   */
+  maxDelay?: number
+}
+/** Usage of the prepared statement cache of a connection. */
+export interface StatementCacheStats {
+  /** @type {number} hits - Statements reused from the cache instead of being compiled. */
+  hits: number
+  /** @type {number} misses - Statements compiled because the cache did not hold them. */
+  misses: number
+  /**
+   * @type {number} evictions - Statements finalized to make room for others, or because
+   * the capacity went down.
+   */
+  evictions: number
+  /** @type {number} size - Statements currently in the cache. */
+  size: number
+  /** @type {number} capacity - Most statements the cache holds. */
+  capacity: number
+}
+/** Describes a column returned by a statement. */
+export interface ColumnInfo {
+  /**
+   * The name of the column in the result set, including its alias.
+   * @type {string} name
+   */
+  name: string
+  /**
+   * The name of the column in its table, missing for expressions.
+   * @type {string} [column]
+   */
+  column?: string
+  /**
+   * The table the column comes from, missing for expressions.
+   * @type {string} [table]
+   */
+  table?: string
+  /**
+   * The database the table belongs to (`main`, `temp`...), missing for expressions.
+   * @type {string} [database]
+   */
+  database?: string
+  /**
+   * The declared type of the column, missing for expressions.
+   * @type {string} [type]
+   */
+  type?: string
+}
+/** Options for a user-defined function. */
+export interface FunctionOptions {
+  /**
+   * Whether the function always returns the same result for the same arguments,
+   * which lets SQLite use it in indexes and optimize repeated calls.
+   * @type {boolean} [deterministic=false]
+   */
+  deterministic?: boolean
+  /**
+   * Whether the function accepts any number of arguments instead of `fn.length`.
+   * @type {boolean} [varargs=false]
+   */
+  varargs?: boolean
+  /**
+   * Whether the function can only be called from top-level SQL,
+   * and not from views, triggers or schema structures.
+   * @type {boolean} [directOnly=false]
+   */
+  directOnly?: boolean
+}
+/** A row change, given to `update` listeners. */
+export interface UpdateEvent {
+  /** @type {string} op - `insert`, `update` or `delete`. */
+  op: 'insert' | 'update' | 'delete'
+  /** @type {string} database - Name of the database, such as `main` or an attached one. */
+  database: string
+  /** @type {string} table */
+  table: string
+  /** @type {number} rowid - Rowid of the changed row. */
+  rowid: number
+}
+/** A schema migration. */
+export interface Migration {
+  /** @type {number} version - Migrations are applied in ascending order of version. */
+  version: number
+  /** @type {string} name */
+  name: string
+  /** @type {string} up - SQL applying the migration. */
+  up: string
+  /** @type {string} [down] - SQL reverting the migration. */
+  down?: string
+}
+/** Options for `Database.migrate()`. */
+export interface MigrateOptions {
+  /**
+   * Directory holding `<version>-<name>.sql` files, where an optional `-- down` line
+   * separates the SQL applying the migration from the SQL reverting it.
+   * @type {string} [directory]
+   */
+  directory?: string
+  /**
+   * Migrations to apply, instead of reading them from a directory.
+   * @type {Migration[]} [migrations]
+   */
+  migrations?: Array<Migration>
+  /**
+   * Name of the table recording the applied migrations.
+   * @type {string} [table='migrations']
+   */
+  table?: string
+  /**
+   * Version to migrate to, lower versions are reverted with their `down` script. Defaults to the latest one.
+   * @type {number} [to]
+   */
+  to?: number
+}
+/** Outcome of `Database.migrate()`. */
+export interface MigrateResult {
+  /** @type {number} version - The version of the schema after migrating, `0` without migrations. */
+  version: number
+  /** @type {number[]} applied - Versions applied, in order. */
+  applied: Array<number>
+  /** @type {number[]} reverted - Versions reverted, in order. */
+  reverted: Array<number>
+}
+/** Options for `new Database()`, applied when the connection opens. */
+export interface DatabaseOptions {
+  /**
+   * Whether to open the database in read-only mode.
+   * @type {boolean} [readonly=false]
+   */
+  readonly?: boolean
+  /**
+   * Whether to fail instead of creating the database file when it does not exist.
+   * @type {boolean} [fileMustExist=false]
+   */
+  fileMustExist?: boolean
+  /**
+   * Milliseconds to wait for a locked database before failing with `SQLITE_BUSY`.
+   * @type {number} [timeout=5000]
+   */
+  timeout?: number
+  /**
+   * One of `delete`, `truncate`, `persist`, `memory`, `wal` or `off`.
+   * @type {string} [journalMode]
+   */
+  journalMode?: 'delete' | 'truncate' | 'persist' | 'memory' | 'wal' | 'off'
+  /**
+   * One of `off`, `normal`, `full` or `extra`.
+   * @type {string} [synchronous]
+   */
+  synchronous?: 'off' | 'normal' | 'full' | 'extra'
+  /**
+   * Whether to enforce foreign key constraints.
+   * @type {boolean} [foreignKeys]
+   */
+  foreignKeys?: boolean
+  /**
+   * Pages kept in memory when positive, or KiB of memory when negative.
+   * @type {number} [cacheSize]
+   */
+  cacheSize?: number
+  /**
+   * Whether `loadExtension()` may be called, as extensions run arbitrary native code.
+   * @type {boolean} [allowExtensions=false]
+   */
+  allowExtensions?: boolean
+  /**
+   * 32-byte key encrypting the database file, its journal and its WAL. Every page is sealed
+   * with AES-256-GCM, so a wrong key or a modified file is reported instead of read.
+   * Opening an encrypted database requires the same key. Copies made with `backup()`
+   * and `serialize()` are not encrypted.
+   * @type {Buffer} [key]
+   */
+  key?: Buffer
+}
+/** A step of a query plan, as reported by `EXPLAIN QUERY PLAN`. */
+export interface QueryPlan {
+  /** @type {number} id */
+  id: number
+  /**
+   * What SQLite does at this step, such as `SCAN users` or `SEARCH posts USING INDEX posts_user (user_id=?)`.
+   * @type {string} detail
+   */
+  detail: string
+  /**
+   * The steps nested in this one, such as the loops of a subquery.
+   * @type {QueryPlan[]} children
+   */
+  children: Array<QueryPlan>
+}
+/**
+ * Options for `new Pool()`, which also takes the options of `new Database()` for every
+ * connection, except `readonly` and a `journalMode` other than `wal`.
+ */
+export interface PoolOptions {
+  /**
+   * Number of read connections, defaults to the number of CPUs.
+   * @type {number} [readers]
+   */
+  readers?: number
+  /**
+   * Whether INTEGER values are returned as `BigInt` instead of numbers.
+   * @type {boolean} [safeIntegers=false]
+   */
+  safeIntegers?: boolean
+}
+/** Usage of the connections of a pool. */
+export interface PoolStats {
+  /** @type {number} readers - Number of read connections. */
+  readers: number
+  /** @type {number} busy - Number of read connections running or waiting for a query. */
+  busy: number
+  /** @type {number} idle - Number of read connections without any query. */
+  idle: number
+  /** @type {boolean} writerBusy - Whether the write connection is running or waiting for a query. */
+  writerBusy: boolean
+}
+/** Options for `Database.pragma()`. */
+export interface PragmaOptions {
+  /**
+   * Whether to return only the first column of the first row instead of every row.
+   * @type {boolean} [simple=false]
+   */
+  simple?: boolean
+}
+/** Options for `Database.deserialize()`. */
+export interface DeserializeOptions {
+  /**
+   * Whether the database is opened in read-only mode.
+   * @type {boolean} [readonly=false]
+   */
+  readonly?: boolean
+}
+/** Options for `Database.applyChangeset()`. */
+export interface ApplyChangesetOptions {
+  /**
+   * Decides what to do with a change that conflicts with the database, by returning
+   * `omit`, `replace` or `abort`. Without it, the first conflict aborts the whole changeset.
+   * @type {Function} [onConflict]
+   */
+  onConflict?: (conflict: ChangesetConflict) => 'omit' | 'replace' | 'abort'
+}
+/** A change of a changeset that cannot be applied as is, given to `onConflict`. */
+export interface ChangesetConflict {
+  /**
+   * @type {string} reason - `data` when the row to update or delete has other values,
+   * `notfound` when it is missing, `conflict` when the row to insert already exists,
+   * `constraint` and `foreign_key` when the change breaks a constraint.
+   */
+  reason: 'data' | 'notfound' | 'conflict' | 'constraint' | 'foreign_key'
+  /** @type {string} table */
+  table: string
+  /** @type {string} op - `insert`, `update` or `delete`. */
+  op: 'insert' | 'update' | 'delete'
+  /**
+   * @type {unknown[]} [old] - Values of the row before an update or a delete, `null`
+   * for the columns an update does not record.
+   */
+  old?: unknown[]
+  /**
+   * @type {unknown[]} [new] - Values of the row after an insert or an update, `null`
+   * for the columns an update leaves unchanged.
+   */
+  new?: unknown[]
+  /**
+   * @type {unknown[]} [conflicting] - Values of the row currently in the database,
+   * for `data` and `conflict`.
+   */
+  conflicting?: unknown[]
+}
+/** Information about the changes made by a statement. */
+export interface RunResult {
+  /**
+   * The number of rows inserted, updated or deleted.
+   * @type {number} changes
+   */
+  changes: number
+  /**
+   * The rowid of the last row inserted into the database, a `BigInt` when safe integers are enabled.
+   * @type {number | bigint} lastInsertRowid
+   */
+  lastInsertRowid: number | bigint
+}
+/** Definition of a virtual table whose rows come from a JS generator. */
+export interface TableOptions {
+  /**
+   * Names of the columns of the rows.
+   * @type {string[]} columns
+   */
+  columns: Array<string>
+  /**
+   * Names of the parameters, hidden columns that receive the arguments of
+   * `FROM name(...)` or constraints such as `WHERE parameter = ?`.
+   * Default to `$1`, `$2`... for each argument of `rows`.
+   * @type {string[]} [parameters]
+   */
+  parameters?: Array<string>
+  /**
+   * Called with the parameters for each scan of the table, `undefined` for those not given,
+   * and returns an iterator of rows, each one an array of values or an object keyed by column.
+   * @type {Function} rows
+   */
+  rows: (...parameters: any[]) => Iterator<unknown[] | Record<string, unknown>>
+  /**
+   * Whether the table can only be used from top-level SQL,
+   * and not from views, triggers or schema structures.
+   * @type {boolean} [directOnly=false]
+   */
+  directOnly?: boolean
+}
+/** A statement execution, given to `profile` listeners. */
+export interface ProfileEvent {
+  /** @type {string} sql - The SQL text of the statement, without the values of its parameters. */
+  sql: string
+  /** @type {number} duration - Milliseconds the statement took to run. */
+  duration: number
+}
+/**
+ * A BLOB value read and written in place, without loading it in memory.
+ *
+ * Its size is fixed when the value is inserted, for example with `zeroblob(size)`,
+ * and updating the row through SQL makes the following reads and writes fail.
+ */
+export declare class BlobHandle {
+  /**
+   * @type {string} table
+   * @readonly
+   */
+  readonly table: string
+  /**
+   * @type {string} column
+   * @readonly
+   */
+  readonly column: string
+  /**
+   * @type {number} rowid
+   * @readonly
+   */
+  readonly rowid: number
+  /**
+   * @type {boolean} readonly - Whether writing is disabled.
+   * @readonly
+   */
+  readonly readonly: boolean
+  /** @type {number} size - Size of the BLOB in bytes. */
+  get size(): number
+  /**
+   * Reads up to `length` bytes starting at `offset`, fewer when the end of the BLOB is reached.
+   * Fails when `offset` is past the end.
+   * @param {number} offset
+   * @param {number} length
+   * @returns {Buffer} buffer
+   */
+  read(offset: number, length: number): Buffer
+  /**
+   * Overwrites the bytes starting at `offset`. BLOBs cannot grow, so writing
+   * past the end fails without writing anything.
+   * @param {number} offset
+   * @param {Buffer} buffer
+   * @returns {undefined}
+   *
+   * Example:
+   * ```js
+   * const { lastInsertRowid } = db.prepare('INSERT INTO files (data) VALUES (zeroblob(?))').run([upload.length]);
+   * db.openBlob('files', 'data', lastInsertRowid).write(0, upload);
+   * ```
+   */
+  write(offset: number, buffer: Buffer): void
+}
+/** A connection to a SQLite database file or an in-memory database. */
+export declare class Database {
+  /**
+   * @type {string} name - The path given to open the database, or `:memory:`.
+   * @readonly
+   */
+  readonly name: string
+  /**
+   * @type {string} filename - The absolute path of the database file, empty for in-memory databases.
+   * @readonly
+   */
+  readonly filename: string
+  /**
+   * Opens a connection to the database at the given path.
+   * Use `:memory:` to open a private, temporary in-memory database.
+   * @param {string} name - Path of the database file or `:memory:`.
+   * @param {DatabaseOptions} [options]
+   *
+   * Example:
+   * ```js
+   * const db = new Database('./data.db', { journalMode: 'wal', foreignKeys: true });
+   * ```
+   */
+  constructor(name: string, options?: DatabaseOptions | undefined | null)
+  /**
+   * Opens an in-memory database holding a copy of an image returned by `serialize()`.
+   * @param {Buffer} buffer
+   * @param {DeserializeOptions} [options]
+   * @returns {Database} database
+   *
+   * Example:
+   * ```js
+   * const db = Database.deserialize(fs.readFileSync('./fixture.db'), { readonly: true });
+   * ```
+   */
+  static deserialize(buffer: Buffer, options?: DeserializeOptions | undefined | null): Database
+  /** @type {boolean} isOpen - Whether the connection is open. */
+  get isOpen(): boolean
+  /** @type {boolean} inTransaction - Whether a transaction is currently active. */
+  get inTransaction(): boolean
+  /** @type {boolean} readonly - Whether the database was opened in read-only mode. */
+  get readonly(): boolean
+  /**
+   * Executes one or more SQL statements separated by semicolons, without returning rows.
+   * @param {string} sql
+   * @returns {undefined}
+   */
+  exec(sql: string): void
+  /**
+   * Executes one or more SQL statements on the libuv thread pool, without blocking the event loop.
+   * Async queries run one at a time in the order they were made, and until they finish the connection
+   * cannot be closed and its synchronous methods throw. User-defined functions cannot be called from them.
+   * @param {string} sql
+   * @returns {Promise<void>}
+   *
+   * Example:
+   * ```js
+   * await db.execAsync('VACUUM');
+   * ```
+   */
+  execAsync(sql: string): Promise<void>
+  /**
+   * Applies the pending migrations in order of version, each in its own transaction,
+   * and records them in a tracking table. Refuses to run when an applied migration changed.
+   * Passing a lower `to` version reverts the migrations above it with their `down` scripts.
+   * @param {MigrateOptions} options
+   * @returns {MigrateResult} result
+   *
+   * Example:
+   * ```js
+   * // migrations/001-users.sql, migrations/002-posts.sql...
+   * const { version, applied } = db.migrate({ directory: './migrations' });
+   * ```
+   */
+  migrate(options: MigrateOptions): MigrateResult
+  /**
+   * Runs a PRAGMA statement, setting it first when a value is given, and returns its rows.
+   * The name may be prefixed with a schema, such as `main.` or an attached database.
+   * @param {string} name
+   * @param {number | string | boolean} [value]
+   * @param {PragmaOptions} [options]
+   * @returns {unknown} result - Every row, or only the first value when `simple` is set.
+   *
+   * Example:
+   * ```js
+   * db.pragma('cache_size', 32000);
+   * db.pragma('journal_mode', undefined, { simple: true }); // 'wal'
+   * db.pragma('table_info', 'users'); // [{ cid: 0, name: 'id', ... }]
+   * ```
+   */
+  pragma(name: string, value?: number | string | boolean | null, options?: PragmaOptions): unknown
+  /**
+   * Compiles the given SQL into a prepared statement.
+   * @param {string} sql
+   * @returns {Statement} statement
+   *
+   * Example:
+   * ```js
+   * const stmt = db.prepare('SELECT * FROM users WHERE id = ?');
+   * const user = stmt.get([1]);
+   * ```
+   */
+  prepare(sql: string): Statement
+  /**
+   * Changes how many prepared statements the connection keeps compiled, 16 by default.
+   * The least recently used ones are finalized when the cache is full, and `0` disables it.
+   * @param {number} capacity
+   * @returns {this}
+   */
+  setStatementCacheCapacity(this: this, capacity: number): this
+  /**
+   * Returns how often statements were reused from the prepared statement cache
+   * instead of being compiled again since the connection was opened, how many it
+   * evicted, and how many statements the cache holds. Running a statement for the first
+   * time reuses the one compiled by `prepare()` and is not a hit.
+   * @returns {StatementCacheStats} stats
+   *
+   * Example:
+   * ```js
+   * const { hits, misses } = db.statementCacheStats();
+   * metrics.gauge('sqlite.statement_cache.hit_ratio', hits / (hits + misses));
+   * ```
+   */
+  statementCacheStats(): StatementCacheStats
+  /**
+   * Wraps a function so that it runs inside a transaction, which is committed when it returns
+   * and rolled back when it throws. Calling it inside another transaction uses a savepoint instead.
+   * The `deferred`, `immediate` and `exclusive` properties run it with that kind of `BEGIN`.
+   * @param {Function} fn
+   * @returns {Function} transaction
+   *
+   * Example:
+   * ```js
+   * const insertMany = db.transaction((users) => {
+   *   for (const user of users) insert.run(user);
+   * });
+   * insertMany.immediate([['Amniel'], ['Rust']]);
+   * ```
+   */
+  transaction<F extends (...args: any[]) => any>(fn: F): F & { deferred: F; immediate: F; exclusive: F }
+  /**
+   * Registers a JS function that can be called from SQL. Registering a function with the
+   * same name and number of arguments replaces it. Its arguments follow `safeIntegers()`.
+   * @param {string} name
+   * @param {FunctionOptions} [options]
+   * @param {Function} fn - Receives the SQL arguments and returns the result.
+   * @returns {this}
+   *
+   * Example:
+   * ```js
+   * db.function('double', { deterministic: true }, (n) => n * 2);
+   * db.prepare('SELECT double(21) AS answer').get(); // { answer: 42 }
+   * ```
+   */
+  function(name: string, options: FunctionOptions | ((...args: any[]) => unknown), fn?: (...args: any[]) => unknown): this
+  /**
+   * Registers an aggregate function that can be called from SQL, which is also
+   * a window function when `inverse` is given. Its arguments follow `safeIntegers()`.
+   * @param {string} name
+   * @param {AggregateOptions} options
+   * @returns {this}
+   *
+   * Example:
+   * ```js
+   * db.aggregate('weighted_avg', {
+   *   start: () => ({ sum: 0, weight: 0 }),
+   *   step: (acc, value, weight) => ({ sum: acc.sum + value * weight, weight: acc.weight + weight }),
+   *   inverse: (acc, value, weight) => ({ sum: acc.sum - value * weight, weight: acc.weight - weight }),
+   *   result: (acc) => (acc.weight ? acc.sum / acc.weight : null),
+   * });
+   * db.prepare('SELECT weighted_avg(price, quantity) FROM orders').pluck().get();
+   * ```
+   */
+  aggregate(this: this, name: string, options: AggregateOptions): this
+  /**
+   * Defines an eponymous virtual table whose rows are produced by a JS generator each time
+   * a statement reads it, to query in-memory data with SQL and join it with stored tables.
+   * The parameters are given as arguments, as in `FROM name(...)`, and follow `safeIntegers()`.
+   * Defining a table again with the same name replaces it.
+   * @param {string} name
+   * @param {TableOptions} options
+   * @returns {this}
+   *
+   * Example:
+   * ```js
+   * db.table('translations', {
+   *   columns: ['key', 'text'],
+   *   parameters: ['locale'],
+   *   *rows(locale) {
+   *     yield* Object.entries(catalogues[locale] ?? {});
+   *   },
+   * });
+   * db.prepare("SELECT p.id, t.text FROM products p JOIN translations('fr') t ON t.key = p.title_key").all();
+   * ```
+   */
+  table(this: this, name: string, options: TableOptions): this
+  /**
+   * Listens to changes of the database: `update` receives each inserted, updated or deleted
+   * row, `commit` and `rollback` each transaction. Listeners are called asynchronously once
+   * the JS thread is free, including for changes made by async queries, and they do not
+   * keep the process alive. Changes to `WITHOUT ROWID` tables are not reported.
+   * @param {string} event - `update`, `commit` or `rollback`.
+   * @param {Function} listener
+   * @returns {this}
+   *
+   * Example:
+   * ```js
+   * db.on('update', ({ op, table, rowid }) => {
+   *   if (table === 'users') cache.delete(rowid);
+   * });
+   * ```
+   */
+  on<E extends 'update' | 'commit' | 'rollback'>(event: E, listener: E extends 'update' ? (event: UpdateEvent) => void : () => void): this
+  /**
+   * Removes a listener added with `on()`, or every listener of the event when none is given.
+   * @param {string} event - `update`, `commit` or `rollback`.
+   * @param {Function} [listener]
+   * @returns {this}
+   */
+  off(event: 'update' | 'commit' | 'rollback', listener?: (...args: any[]) => void): this
+  /**
+   * Calls `listener` with the SQL of each statement that starts running, or stops when
+   * `null` is given. Like `on()` listeners, it is called asynchronously once the JS thread
+   * is free, and async queries are reported too.
+   * @param {Function | null} listener
+   * @returns {this}
+   */
+  trace(listener: ((sql: string) => void) | null): this
+  /**
+   * Calls `listener` with the SQL and the duration of each statement once it finishes,
+   * or stops when `null` is given. It is called asynchronously like `trace()` listeners.
+   * @param {Function | null} listener
+   * @returns {this}
+   *
+   * Example:
+   * ```js
+   * db.profile(({ sql, duration }) => {
+   *   if (duration > 100) logger.warn({ sql, duration }, 'slow query');
+   * });
+   * ```
+   */
+  profile(listener: ((event: ProfileEvent) => void) | null): this
+  /**
+   * Waits up to `ms` milliseconds for other connections to release their locks before
+   * failing with `SQLITE_BUSY`, replacing any `busyHandler()`. `0` fails right away.
+   * @param {number} ms
+   * @returns {this}
+   */
+  busyTimeout(this: this, ms: number): this
+  /**
+   * Decides how statements wait for other connections to release their locks, replacing
+   * any `busyTimeout()`. The handler receives how many times the statement already retried
+   * and returns whether to keep waiting, which it does with an exponential backoff. If the
+   * handler throws, the statement stops waiting and throws the same exception. Without
+   * a handler, statements retry `retries` times. Async queries cannot call the handler, and
+   * stop after `retries` as well.
+   * @param {Function | null} handler
+   * @param {BusyHandlerOptions} [options]
+   * @returns {this}
+   *
+   * Example:
+   * ```js
+   * db.busyHandler((retries) => retries < 20, { delay: 5, maxDelay: 250 });
+   * ```
+   */
+  busyHandler(handler: ((retries: number) => boolean) | null, options?: BusyHandlerOptions): this
+  /**
+   * Copies the database into the file at `destination` while it stays in use, which is
+   * safe with WAL unlike copying the file. The connection cannot be closed until it finishes.
+   * @param {string} destination - Path of the backup file, overwritten if it exists.
+   * @param {BackupOptions} [options]
+   * @returns {Promise<void>}
+   *
+   * Example:
+   * ```js
+   * await db.backup(`backup-${Date.now()}.db`, {
+   *   progress: ({ totalPages, remainingPages }) => console.log(`${remainingPages}/${totalPages}`),
+   * });
+   * ```
+   */
+  backup(destination: string, options?: BackupOptions | undefined | null): Promise<void>
+  /**
+   * Inserts the records of a CSV file into `table` with a single prepared statement,
+   * inside a transaction so that either every record is inserted or none is.
+   * @param {string} table
+   * @param {string} path
+   * @param {CsvImportOptions} [options]
+   * @returns {number} count - Number of records inserted.
+   *
+   * Example:
+   * ```js
+   * db.importCsv('users', './users.csv', {
+   *   columns: { 'E-mail': 'email', Name: 'name', Active: 'active' },
+   *   types: { active: 'boolean' },
+   * });
+   * ```
+   */
+  importCsv(table: string, path: string, options?: CsvImportOptions | undefined | null): number
+  /**
+   * Inserts objects into `table` with a single prepared statement, inside a transaction so
+   * that either every row is inserted or none is. Missing fields are inserted as NULL.
+   * @param {string} table
+   * @param {object[]} rows
+   * @param {ImportOptions} [options]
+   * @returns {number} count - Number of rows inserted.
+   *
+   * Example:
+   * ```js
+   * db.importJson('events', JSON.parse(body), { columns: { id: 'id', at: 'created_at' } });
+   * ```
+   */
+  importJson(table: string, rows: Record<string, unknown>[], options?: ImportOptions): number
+  /**
+   * Writes the rows returned by `sql` to a CSV file, replacing it if it exists.
+   * BLOBs are written in hexadecimal.
+   * @param {string} sql
+   * @param {string} path
+   * @param {CsvExportOptions} [options]
+   * @returns {number} count - Number of rows written.
+   */
+  exportCsv(sql: string, path: string, options?: CsvExportOptions | undefined | null): number
+  /**
+   * Starts recording the changes made to `tables`, or to every table, so they can be sent
+   * to another database as a changeset. Only tables with a PRIMARY KEY are recorded.
+   * The connection cannot be closed until the session is.
+   * @param {string[]} [tables]
+   * @returns {Session} session
+   *
+   * Example:
+   * ```js
+   * const session = db.createSession(['todos', 'lists']);
+   * // ...work offline...
+   * await upload(session.changeset());
+   * session.close();
+   * ```
+   */
+  createSession(tables?: Array<string> | undefined | null): Session
+  /**
+   * Applies a changeset or a patchset created by a session, in a single transaction.
+   * Conflicting changes abort it unless `onConflict` resolves them, and the values it
+   * receives follow `safeIntegers()`.
+   * @param {Buffer} changeset
+   * @param {ApplyChangesetOptions} [options]
+   * @returns {undefined}
+   *
+   * Example:
+   * ```js
+   * db.applyChangeset(await download(), {
+   *   onConflict: ({ reason }) => (reason === 'data' ? 'replace' : 'omit'),
+   * });
+   * ```
+   */
+  applyChangeset(changeset: Buffer, options?: ApplyChangesetOptions | undefined | null): void
+  /**
+   * Loads a SQLite extension from a shared library. Extensions run arbitrary native code,
+   * so the database must be opened with `allowExtensions: true`.
+   * @param {string} path - Path of the library, the platform suffix such as `.so` can be omitted.
+   * @param {string} [entryPoint] - Name of the init function, derived from the file name by default.
+   * @returns {this}
+   *
+   * Example:
+   * ```js
+   * const db = new Database('./geo.db', { allowExtensions: true });
+   * db.loadExtension('./mod_spatialite');
+   * ```
+   */
+  loadExtension(this: this, path: string, entryPoint?: string | undefined | null): this
+  /**
+   * Opens a BLOB for incremental reads and writes, to stream large values without
+   * loading them in memory. `createReadStream()` and `createWriteStream()` adapt it to Node streams.
+   * @param {string} table
+   * @param {string} column
+   * @param {number | bigint} rowid
+   * @param {BlobOptions} [options]
+   * @returns {BlobHandle} blob
+   *
+   * Example:
+   * ```js
+   * const blob = db.openBlob('attachments', 'data', id, { readonly: true });
+   * blob.createReadStream().pipe(res);
+   * ```
+   */
+  openBlob(table: string, column: string, rowid: number | bigint, options?: BlobOptions | undefined | null): BlobHandle
+  /**
+   * Re-encrypts a database opened with a `key` using a new one, by rewriting every page
+   * in a single transaction. Other connections must reopen the database with the new key.
+   * If the process stops halfway, opening the database with the old key rolls the change back.
+   * @param {Buffer} key - The new 32-byte key.
+   * @returns {undefined}
+   *
+   * Example:
+   * ```js
+   * const db = new Database('./cache.db', { key: oldKey });
+   * db.rekey(crypto.randomBytes(32));
+   * ```
+   */
+  rekey(key: Buffer): void
+  /**
+   * Returns a copy of the content of the database as a Buffer, which can be
+   * written to a file or opened again with `Database.deserialize()`.
+   * @param {string} [schema='main'] - Name of the database, such as `temp` or an attached one.
+   * @returns {Buffer} buffer
+   */
+  serialize(schema?: string | undefined | null): Buffer
+  /**
+   * Returns INTEGER values as `BigInt` in every statement prepared from now on.
+   * Each statement can still change it with `Statement.safeIntegers()`.
+   * @param {boolean} [toggle=true]
+   * @returns {this}
+   */
+  safeIntegers(this: this, toggle?: boolean | undefined | null): this
+  /**
+   * Closes the connection. Calling it on a closed connection does nothing.
+   * @returns {undefined}
+   */
+  close(): void
+}
+/** Iterator over the rows of a statement, created with `Statement.iterate(params)`. */
+export declare class StatementIterator {
+  [Symbol.iterator](): Iterator<Step, void, void>
+}
+/**
+ * A pool of connections to a database file in WAL mode, with several readers
+ * and a single writer, running every query on the libuv thread pool.
+ *
+ * Reads go to an idle reader in round-robin order, or queue on the next reader when they
+ * are all busy. Writes queue on the writer and run one after the other, on a single thread
+ * of the libuv pool, so they never keep reads from running. At most `UV_THREADPOOL_SIZE`
+ * queries run at the same time, 4 by default.
+ */
+export declare class Pool {
+  /**
+   * @type {string} filename - The absolute path of the database file.
+   * @readonly
+   */
+  readonly filename: string
+  /**
+   * Opens the writer and the readers, switching the database to WAL mode.
+   * @param {string} name - Path of the database file, created if it does not exist.
+   * @param {PoolOptions & DatabaseOptions} [options]
+   *
+   * Example:
+   * ```js
+   * const pool = new Pool('./data.db', { readers: 4, timeout: 10000, key });
+   * const users = await pool.all('SELECT * FROM users WHERE active = ?', [1]);
+   * ```
+   */
+  constructor(name: string, options?: PoolOptions & DatabaseOptions)
+  /**
+   * Runs a read query on a reader and returns every row.
+   * @param {string} sql
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {Promise<unknown[]>} rows
+   */
+  all(sql: string, params?: unknown[] | Record<string, unknown>): Promise<Array<unknown>>
+  /**
+   * Runs a read query on a reader and returns the first row, or `null` if there is none.
+   * @param {string} sql
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {Promise<unknown>} row
+   */
+  get(sql: string, params?: unknown[] | Record<string, unknown>): Promise<unknown>
+  /**
+   * Runs a statement on the writer, after every write queued before it.
+   * @param {string} sql
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {Promise<RunResult>} info
+   */
+  run(sql: string, params?: unknown[] | Record<string, unknown>): Promise<RunResult>
+  /**
+   * Executes one or more SQL statements on the writer, after every write queued before them.
+   * @param {string} sql
+   * @returns {Promise<void>}
+   */
+  exec(sql: string): Promise<void>
+  /**
+   * Returns how many connections are in use.
+   * @returns {PoolStats} stats
+   */
+  stats(): PoolStats
+  /**
+   * Closes every connection of the pool.
+   * @returns {undefined}
+   */
+  close(): void
+}
+/**
+ * Records the changes made to the tables of a database, to replay them elsewhere with
+ * `Database.applyChangeset()`. Only tables with a PRIMARY KEY are recorded.
+ * The connection cannot be closed until the session is.
+ */
+export declare class Session {
+  /**
+   * Returns the changes recorded so far, as a changeset holding the old and new values
+   * of each changed row. Several changes of the same row are merged into one.
+   * @returns {Buffer} changeset
+   *
+   * Example:
+   * ```js
+   * const session = db.createSession(['todos']);
+   * db.prepare('UPDATE todos SET done = 1 WHERE id = ?').run([id]);
+   * await fetch('/sync', { method: 'POST', body: session.changeset() });
+   * ```
+   */
+  changeset(): Buffer
+  /**
+   * Returns the changes recorded so far as a patchset, a smaller changeset without the
+   * old values of updated and deleted rows, so conflicts are only detected on primary keys.
+   * @returns {Buffer} patchset
+   */
+  patchset(): Buffer
+  /** @type {boolean} isEmpty - Whether no change was recorded. */
+  get isEmpty(): boolean
+  /**
+   * Pauses or resumes recording, for example while applying changes received from elsewhere.
+   * @param {boolean} [toggle=true]
+   * @returns {this}
+   */
+  enable(this: this, toggle?: boolean | undefined | null): this
+  /**
+   * Stops recording and releases the connection. Calling it again does nothing.
+   * @returns {undefined}
+   */
+  close(): void
+}
+/**
+ * A prepared statement, created with `Database.prepare(sql)`.
+ *
+ * Statements are backed by the connection's prepared statement cache, so running
+ * the same statement again does not compile its SQL twice.
+ */
+export declare class Statement {
+  /**
+   * @type {string} source - The SQL text of the statement.
+   * @readonly
+   */
+  readonly source: string
+  /**
+   * @type {boolean} reader - Whether the statement returns data.
+   * @readonly
+   */
+  readonly reader: boolean
+  /**
+   * @type {boolean} readonly - Whether the statement leaves the database unchanged.
+   * @readonly
+   */
+  readonly readonly: boolean
+  /**
+   * Executes the statement, discarding any rows it returns.
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {RunResult} info
+   */
+  run(params?: unknown[] | Record<string, unknown>): RunResult
+  /**
+   * Executes the statement on the libuv thread pool, discarding any rows it returns,
+   * after the async queries made before it. User-defined functions cannot be called from it.
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {Promise<RunResult>} info
+   */
+  runAsync(params?: unknown[] | Record<string, unknown>): Promise<RunResult>
+  /**
+   * Executes the statement and returns the first row, or `null` if there is none.
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {unknown} row
+   */
+  get(params?: unknown[] | Record<string, unknown>): unknown
+  /**
+   * Executes the statement and returns every row.
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {unknown[]} rows
+   */
+  all(params?: unknown[] | Record<string, unknown>): Array<unknown>
+  /**
+   * Executes the statement on the libuv thread pool and returns every row,
+   * after the async queries made before it. User-defined functions cannot be called from it.
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {Promise<unknown[]>} rows
+   *
+   * Example:
+   * ```js
+   * const report = await db.prepare('SELECT * FROM sales WHERE year = ?').allAsync([2024]);
+   * ```
+   */
+  allAsync(params?: unknown[] | Record<string, unknown>): Promise<Array<unknown>>
+  /**
+   * Executes the statement and returns an iterator that reads rows one at a time.
+   * The connection cannot be closed until the iterator finishes or is stopped with `return()`.
+   * @param {unknown[] | Record<string, unknown>} [params]
+   * @returns {StatementIterator} iterator
+   *
+   * Example:
+   * ```js
+   * for (const user of db.prepare('SELECT * FROM users').iterate()) {
+   *   if (user.name === 'Amniel') break;
+   * }
+   * ```
+   */
+  iterate(params?: unknown[] | Record<string, unknown>): StatementIterator
+  /**
+   * Returns rows as arrays of values instead of objects.
+   * @param {boolean} [toggle=true]
+   * @returns {this}
+   */
+  raw(this: this, toggle?: boolean | undefined | null): this
+  /**
+   * Returns only the value of the first column instead of the whole row.
+   * @param {boolean} [toggle=true]
+   * @returns {this}
+   */
+  pluck(this: this, toggle?: boolean | undefined | null): this
+  /**
+   * Returns rows as objects keyed by table name, each holding the columns of that table.
+   * Columns that do not come from a table are stored under `$`.
+   * @param {boolean} [toggle=true]
+   * @returns {this}
+   *
+   * Example:
+   * ```js
+   * const row = db.prepare('SELECT users.*, posts.*, 1 AS one FROM users JOIN posts ON ...').expand().get();
+   * // { users: { id, name }, posts: { id, title }, $: { one: 1 } }
+   * ```
+   */
+  expand(this: this, toggle?: boolean | undefined | null): this
+  /**
+   * Returns INTEGER values as `BigInt` instead of numbers, which lose precision above 2^53.
+   * Defaults to the setting of the database when the statement was prepared.
+   * @param {boolean} [toggle=true]
+   * @returns {this}
+   */
+  safeIntegers(this: this, toggle?: boolean | undefined | null): this
+  /**
+   * Describes the columns returned by the statement.
+   * @returns {ColumnInfo[]} columns
+   */
+  columns(): Array<ColumnInfo>
+  /**
+   * Describes how SQLite runs the statement, as the tree of steps reported by
+   * `EXPLAIN QUERY PLAN`. The plan does not depend on the values of the parameters.
+   * @returns {QueryPlan[]} plan
+   *
+   * Example:
+   * ```js
+   * db.prepare('SELECT * FROM posts WHERE user_id = ?').explain();
+   * // [{ id: 3, detail: 'SEARCH posts USING INDEX posts_user (user_id=?)', children: [] }]
+   * ```
+   */
+  explain(): Array<QueryPlan>
+}
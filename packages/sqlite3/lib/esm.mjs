@@ -1,2 +1,2 @@
-import _bindings from './bindings.js';
+import _bindings from './index.js';
 export default _bindings;
\ No newline at end of file
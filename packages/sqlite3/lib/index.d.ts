@@ -1,6 +1,61 @@
-/* tslint:disable */
-/* eslint-disable */
+import type { Readable, Writable } from 'node:stream';
 
-/* auto-generated by NAPI-RS */
+export * from './bindings';
 
-export declare function floor(n: number): number
+/**
+ * Errors reported by SQLite, such as constraint violations or a locked database.
+ *
+ * Example:
+ * ```js
+ * try {
+ *   insert.run([email]);
+ * } catch (err) {
+ *   if (err.code !== 'SQLITE_CONSTRAINT_UNIQUE') throw err;
+ * }
+ * ```
+ */
+export interface SqliteError extends Error {
+  /** Name of the extended result code, such as `SQLITE_BUSY` or `SQLITE_CONSTRAINT_UNIQUE`. */
+  code: string;
+  /** Primary result code, such as `5` for `SQLITE_BUSY` or `19` for `SQLITE_CONSTRAINT`. */
+  errno: number;
+  /** Extended result code, such as `2067` for `SQLITE_CONSTRAINT_UNIQUE`. */
+  extendedCode: number;
+  /** Offset in the SQL of the token that failed to prepare, when SQLite knows it. */
+  offset?: number;
+}
+
+/** Options for `BlobHandle.createReadStream()` and `BlobHandle.createWriteStream()`. */
+export interface BlobStreamOptions {
+  /** Offset of the first byte to read or write. */
+  start?: number;
+  /** Offset after the last byte to read, the end of the BLOB by default. */
+  end?: number;
+  /** Size of the chunks, 64 KiB by default. */
+  highWaterMark?: number;
+}
+
+declare module './bindings' {
+  interface BlobHandle {
+    /**
+     * Reads the BLOB as a stream of Buffer chunks.
+     *
+     * Example:
+     * ```js
+     * db.openBlob('attachments', 'data', id, { readonly: true }).createReadStream().pipe(res);
+     * ```
+     */
+    createReadStream(options?: BlobStreamOptions): Readable;
+
+    /**
+     * Writes the chunks of a stream into the BLOB, which must already have its final size.
+     *
+     * Example:
+     * ```js
+     * const { lastInsertRowid } = db.prepare('INSERT INTO attachments (data) VALUES (zeroblob(?))').run([size]);
+     * await pipeline(req, db.openBlob('attachments', 'data', lastInsertRowid).createWriteStream());
+     * ```
+     */
+    createWriteStream(options?: Omit<BlobStreamOptions, 'end'>): Writable;
+  }
+}
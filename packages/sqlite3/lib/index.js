@@ -1 +1,47 @@
-module.exports = require('./bindings.js');
\ No newline at end of file
+const { Readable, Writable } = require('node:stream');
+const bindings = require('./bindings.js');
+
+const DEFAULT_CHUNK_SIZE = 64 * 1024;
+
+bindings.BlobHandle.prototype.createReadStream = function createReadStream(options = {}) {
+  const blob = this;
+  const end = Math.min(options.end ?? Infinity, blob.size);
+  let offset = options.start ?? 0;
+
+  return new Readable({
+    highWaterMark: options.highWaterMark ?? DEFAULT_CHUNK_SIZE,
+    read(size) {
+      try {
+        const chunk = offset < end ? blob.read(offset, Math.min(size, end - offset)) : null;
+        if (chunk && chunk.length > 0) {
+          offset += chunk.length;
+          this.push(chunk);
+        } else {
+          this.push(null);
+        }
+      } catch (err) {
+        this.destroy(err);
+      }
+    },
+  });
+};
+
+bindings.BlobHandle.prototype.createWriteStream = function createWriteStream(options = {}) {
+  const blob = this;
+  let offset = options.start ?? 0;
+
+  return new Writable({
+    highWaterMark: options.highWaterMark ?? DEFAULT_CHUNK_SIZE,
+    write(chunk, _encoding, callback) {
+      try {
+        blob.write(offset, chunk);
+        offset += chunk.length;
+        callback();
+      } catch (err) {
+        callback(err);
+      }
+    },
+  });
+};
+
+module.exports = bindings;
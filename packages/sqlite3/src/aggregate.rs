@@ -0,0 +1,165 @@
+use super::{
+  error,
+  function::{user_error, Callback, FunctionOptions},
+  reference::Slot,
+  value,
+};
+use napi::{Env, JsFunction, JsUnknown, Result, ValueType};
+use napi_derive::napi;
+use rusqlite::{
+  functions::{Aggregate, Context, WindowAggregate},
+  types::Value,
+  Connection,
+};
+
+/// Definition of a user-defined aggregate function.
+#[napi(object, object_to_js = false)]
+pub struct AggregateOptions {
+  /// The initial value of the accumulator, or a function returning it for each group.
+  /// @type {unknown} [start=null]
+  pub start: Option<JsUnknown>,
+
+  /// Adds a row to the accumulator, called with the accumulator followed by the SQL arguments.
+  /// Its return value replaces the accumulator, unless it is `undefined`.
+  /// @type {Function} step
+  pub step: JsFunction,
+
+  /// Removes a row from the accumulator, with the same signature as `step`.
+  /// Supplying it makes the aggregate usable as a window function.
+  /// @type {Function} [inverse]
+  pub inverse: Option<JsFunction>,
+
+  /// Computes the result from the accumulator, which is returned as is when missing.
+  /// @type {Function} [result]
+  pub result: Option<JsFunction>,
+
+  /// @type {boolean} [deterministic=false]
+  pub deterministic: Option<bool>,
+
+  /// Whether the function accepts any number of arguments instead of `step.length - 1`.
+  /// @type {boolean} [varargs=false]
+  pub varargs: Option<bool>,
+
+  /// @type {boolean} [directOnly=false]
+  pub direct_only: Option<bool>,
+}
+
+/// The JS side of an aggregate, called by SQLite for every group.
+struct Aggregator {
+  env: Env,
+  start: Slot,
+  step: Callback,
+  inverse: Option<Callback>,
+  result: Option<Callback>,
+}
+
+impl Aggregator {
+  /// Creates the accumulator of a new group.
+  fn start(&self) -> Result<Slot> {
+    let start = self.start.get()?;
+    let value = if start.get_type()? == ValueType::Function {
+      unsafe { start.cast::<JsFunction>() }.call::<JsUnknown>(None, &[])?
+    } else {
+      start
+    };
+    Slot::new(&self.env, value)
+  }
+
+  /// Calls `callback` with the accumulator and the SQL arguments, keeping its return value.
+  fn update(callback: &Callback, ctx: &Context, acc: &Slot) -> Result<()> {
+    let mut args = vec![acc.get()?];
+    args.extend(callback.arguments(ctx)?);
+
+    let value = callback.call(&args)?;
+    if value.get_type()? != ValueType::Undefined {
+      acc.set(value)?;
+    }
+    Ok(())
+  }
+
+  /// Computes the result of a group.
+  fn result(&self, acc: &Slot) -> Result<Value> {
+    let value = match &self.result {
+      Some(result) => result.call(&[acc.get()?])?,
+      None => acc.get()?,
+    };
+    value::to_value(value)
+  }
+}
+
+impl Aggregate<Slot, Value> for Aggregator {
+  fn init(&self, _: &mut Context<'_>) -> rusqlite::Result<Slot> {
+    self.start().map_err(user_error)
+  }
+
+  fn step(&self, ctx: &mut Context<'_>, acc: &mut Slot) -> rusqlite::Result<()> {
+    Aggregator::update(&self.step, ctx, acc).map_err(user_error)
+  }
+
+  fn finalize(&self, _: &mut Context<'_>, acc: Option<Slot>) -> rusqlite::Result<Value> {
+    let acc = match acc {
+      Some(acc) => acc,
+      None => self.start().map_err(user_error)?,
+    };
+    self.result(&acc).map_err(user_error)
+  }
+}
+
+impl WindowAggregate<Slot, Value> for Aggregator {
+  fn value(&self, acc: Option<&mut Slot>) -> rusqlite::Result<Value> {
+    match acc {
+      Some(acc) => self.result(acc),
+      None => self.start().and_then(|acc| self.result(&acc)),
+    }
+    .map_err(user_error)
+  }
+
+  fn inverse(&self, ctx: &mut Context<'_>, acc: &mut Slot) -> rusqlite::Result<()> {
+    let inverse = self.inverse.as_ref().expect("window functions have an inverse");
+    Aggregator::update(inverse, ctx, acc).map_err(user_error)
+  }
+}
+
+/// Registers an aggregate function called `name`, which is also a window function
+/// when `inverse` is given.
+///
+/// # Errors
+///
+/// Returns an Error if the callbacks cannot be referenced or SQLite rejects the function.
+pub fn create(
+  env: &Env,
+  conn: &Connection,
+  name: &str,
+  options: AggregateOptions,
+  safe_integers: bool,
+) -> Result<()> {
+  let function = FunctionOptions {
+    deterministic: options.deterministic,
+    varargs: options.varargs,
+    direct_only: options.direct_only,
+  };
+  let arity = match function.arity(env, &options.step)? {
+    -1 => -1,
+    n => (n - 1).max(0),
+  };
+
+  let start = match options.start {
+    Some(start) => start,
+    None => env.get_null()?.into_unknown(),
+  };
+  let callback = |function: &JsFunction| Callback::new(env, function, safe_integers);
+  let aggregator = Aggregator {
+    env: *env,
+    start: Slot::new(env, start)?,
+    step: callback(&options.step)?,
+    inverse: options.inverse.as_ref().map(callback).transpose()?,
+    result: options.result.as_ref().map(callback).transpose()?,
+  };
+
+  if aggregator.inverse.is_some() {
+    conn.create_window_function(name, arity, function.flags(), aggregator)
+  } else {
+    conn.create_aggregate_function(name, arity, function.flags(), aggregator)
+  }
+  .map_err(error::sqlite)
+}
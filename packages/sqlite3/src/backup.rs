@@ -0,0 +1,186 @@
+use super::{connection::Handle, error, function::Callback};
+use napi::{
+  bindgen_prelude::Undefined,
+  threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+  CallContext, Env, Error, JsFunction, JsUnknown, Result, Task,
+};
+use napi_derive::napi;
+use rusqlite::{
+  backup::{Backup, StepResult},
+  Connection,
+};
+use std::{
+  sync::mpsc::{self, Sender},
+  thread,
+  time::Duration,
+};
+
+/// Pages copied on each step when `pages` is not given.
+const DEFAULT_PAGES: i32 = 100;
+
+/// How long to wait before retrying a step while the source database is locked.
+const BUSY_DELAY: Duration = Duration::from_millis(10);
+
+/// Options for `Database.backup()`.
+#[napi(object, object_to_js = false)]
+pub struct BackupOptions {
+  /// Number of pages copied on each step, `0` or less copies the whole database in a single step.
+  /// @type {number} [pages=100]
+  pub pages: Option<i32>,
+
+  /// Called after each step with the progress of the backup.
+  /// @type {Function} [progress]
+  #[napi(ts_type = "(progress: BackupProgress) => void")]
+  pub progress: Option<JsFunction>,
+}
+
+/// Progress of a backup, given to the `progress` callback.
+#[napi(object)]
+pub struct BackupProgress {
+  /// @type {number} totalPages - Number of pages in the source database.
+  pub total_pages: i32,
+
+  /// @type {number} remainingPages - Number of pages left to copy.
+  pub remaining_pages: i32,
+}
+
+/// A progress report sent to the JS thread, with the channel receiving what the callback did.
+type Report = (BackupProgress, Sender<Result<()>>);
+
+/// Copies the database into another file on the libuv thread pool, one step at a time.
+///
+/// The connection is only locked while a step runs, so it stays usable in between,
+/// and it cannot be closed until the backup finishes.
+pub struct BackupTask {
+  handle: Handle,
+  destination: String,
+  pages: i32,
+  progress: Option<ThreadsafeFunction<Report, ErrorStrategy::CalleeHandled>>,
+  failure: Option<error::Details>,
+}
+
+impl BackupTask {
+  /// Prepares a backup of the connection into `destination`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the connection is closed or the progress callback cannot be referenced.
+  pub fn new(env: &Env, handle: Handle, destination: String, options: Option<BackupOptions>) -> Result<Self> {
+    if !handle.is_open() {
+      return Err(error::not_open());
+    }
+
+    let (pages, progress) = options.map_or((None, None), |options| (options.pages, options.progress));
+    let progress = progress
+      .map(|progress| {
+        // The callback is called from the context of the threadsafe function, which then
+        // calls a function doing nothing, so that its exception can fail the backup.
+        let callback = Callback::new(env, &progress, false)?;
+        env
+          .create_function_from_closure("progress", |ctx: CallContext| ctx.env.get_undefined())?
+          .create_threadsafe_function(0, move |ctx: ThreadSafeCallContext<Report>| {
+            let (progress, done) = ctx.value;
+            let result = callback
+              .convert(progress)
+              .and_then(|progress| callback.call(&[progress]));
+            let _ = done.send(result.map(drop));
+            Ok(Vec::<JsUnknown>::new())
+          })
+      })
+      .transpose()?;
+
+    handle.acquire();
+    Ok(BackupTask {
+      handle,
+      destination,
+      pages: match pages.unwrap_or(DEFAULT_PAGES) {
+        pages if pages > 0 => pages,
+        _ => -1,
+      },
+      progress,
+      failure: None,
+    })
+  }
+
+  /// Calls the progress callback and waits until it returns, so every call
+  /// happens before the backup resolves.
+  ///
+  /// # Errors
+  ///
+  /// Returns the exception thrown by the callback.
+  fn report(&self, total_pages: i32, remaining_pages: i32) -> Result<()> {
+    let Some(progress) = &self.progress else {
+      return Ok(());
+    };
+
+    let (done, wait) = mpsc::channel();
+    let progress_report = BackupProgress {
+      total_pages,
+      remaining_pages,
+    };
+    progress.call(Ok((progress_report, done)), ThreadsafeFunctionCallMode::Blocking);
+    // Fails once the callback is dropped without running, which is fine to ignore.
+    wait.recv().unwrap_or(Ok(()))
+  }
+
+  /// Copies the database, stepping until every page is copied.
+  fn copy(&self) -> Result<()> {
+    let mut destination = Connection::open(&self.destination).map_err(error::sqlite)?;
+
+    let guard = self.handle.lock();
+    let slot = guard.borrow();
+    let source = slot.as_ref().ok_or_else(error::not_open)?;
+    // SAFETY: the connection cannot be closed while the backup is acquired, see
+    // `Handle::close`. SQLite only uses it while the backup is initialized, stepped
+    // or finished, which always happens with the lock held.
+    let source = unsafe { &*(source as *const Connection) };
+    let backup = Backup::new(source, &mut destination).map_err(error::sqlite)?;
+    drop(slot);
+    drop(guard);
+
+    loop {
+      let guard = self.handle.lock();
+      let step = backup.step(self.pages);
+      let progress = backup.progress();
+      if !matches!(step, Ok(StepResult::More | StepResult::Busy | StepResult::Locked)) {
+        drop(backup);
+        drop(guard);
+        return step.map(|_| ()).map_err(error::sqlite);
+      }
+      drop(guard);
+
+      if let Ok(StepResult::More) = step {
+        if let Err(err) = self.report(progress.pagecount, progress.remaining) {
+          let _guard = self.handle.lock();
+          drop(backup);
+          return Err(err);
+        }
+      } else {
+        thread::sleep(BUSY_DELAY);
+      }
+    }
+  }
+}
+
+impl Task for BackupTask {
+  type Output = ();
+  type JsValue = Undefined;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    self.copy().inspect_err(|err| self.failure = error::take(err))
+  }
+
+  fn resolve(&mut self, _: Env, _: Self::Output) -> Result<Self::JsValue> {
+    Ok(())
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    Err(error::reject(&env, err, self.failure.take()))
+  }
+
+  fn finally(&mut self, _: Env) -> Result<()> {
+    self.progress.take();
+    self.handle.release();
+    Ok(())
+  }
+}
@@ -0,0 +1,146 @@
+use super::{connection::Handle, error, serialize};
+use napi::{bindgen_prelude::Buffer, Error, Result, Status};
+use napi_derive::napi;
+use rusqlite::blob::Blob;
+
+/// Options for `Database.openBlob()`.
+#[napi(object)]
+#[derive(Default)]
+pub struct BlobOptions {
+  /// Whether to open the BLOB for reading only.
+  /// @type {boolean} [readonly=false]
+  pub readonly: Option<bool>,
+
+  /// Name of the database holding the table, such as `temp` or an attached one.
+  /// @type {string} [database='main']
+  pub database: Option<String>,
+}
+
+/// A BLOB value read and written in place, without loading it in memory.
+///
+/// Its size is fixed when the value is inserted, for example with `zeroblob(size)`,
+/// and updating the row through SQL makes the following reads and writes fail.
+#[napi]
+pub struct BlobHandle {
+  /// @type {string} table
+  /// @readonly
+  #[napi(readonly)]
+  pub table: String,
+
+  /// @type {string} column
+  /// @readonly
+  #[napi(readonly)]
+  pub column: String,
+
+  /// @type {number} rowid
+  /// @readonly
+  #[napi(readonly)]
+  pub rowid: i64,
+
+  /// @type {boolean} readonly - Whether writing is disabled.
+  /// @readonly
+  #[napi(readonly)]
+  pub readonly: bool,
+
+  database: Option<String>,
+  handle: Handle,
+}
+
+impl BlobHandle {
+  /// Checks that the BLOB can be opened, so a missing row fails early.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the connection is closed or the value is not a BLOB or TEXT.
+  pub fn new(handle: Handle, table: String, column: String, rowid: i64, options: BlobOptions) -> Result<Self> {
+    let blob = BlobHandle {
+      table,
+      column,
+      rowid,
+      readonly: options.readonly.unwrap_or(false),
+      database: options.database,
+      handle,
+    };
+    blob.with(|_| Ok(()))?;
+    Ok(blob)
+  }
+
+  /// Runs `f` with the BLOB opened under the connection lock. Opening it on each call
+  /// keeps the connection free to close, and picks up the row again after it moves.
+  fn with<T, F>(&self, f: F) -> Result<T>
+  where
+    F: FnOnce(&mut Blob<'_>) -> Result<T>,
+  {
+    self.handle.with(|conn| {
+      let mut blob = conn
+        .blob_open(
+          serialize::schema(self.database.as_deref()),
+          &self.table,
+          &self.column,
+          self.rowid,
+          self.readonly,
+        )
+        .map_err(error::sqlite)?;
+      f(&mut blob)
+    })
+  }
+}
+
+#[napi]
+impl BlobHandle {
+  /// @type {number} size - Size of the BLOB in bytes.
+  #[napi(getter)]
+  pub fn size(&self) -> Result<u32> {
+    self.with(|blob| Ok(blob.len() as u32))
+  }
+
+  /// Reads up to `length` bytes starting at `offset`, fewer when the end of the BLOB is reached.
+  /// Fails when `offset` is past the end.
+  /// @param {number} offset
+  /// @param {number} length
+  /// @returns {Buffer} buffer
+  #[napi]
+  pub fn read(&self, offset: u32, length: u32) -> Result<Buffer> {
+    self.with(|blob| {
+      let offset = offset as usize;
+      if offset > blob.len() {
+        return Err(out_of_range(offset, 0, blob.len()));
+      }
+      // Only allocate what is left, as rusqlite cannot read more than `i32::MAX` bytes at once.
+      let mut buffer = vec![0; (length as usize).min(blob.len() - offset)];
+      let read = blob.read_at(&mut buffer, offset).map_err(error::sqlite)?;
+      buffer.truncate(read);
+      Ok(buffer.into())
+    })
+  }
+
+  /// Overwrites the bytes starting at `offset`. BLOBs cannot grow, so writing
+  /// past the end fails without writing anything.
+  /// @param {number} offset
+  /// @param {Buffer} buffer
+  /// @returns {undefined}
+  ///
+  /// Example:
+  /// ```js
+  /// const { lastInsertRowid } = db.prepare('INSERT INTO files (data) VALUES (zeroblob(?))').run([upload.length]);
+  /// db.openBlob('files', 'data', lastInsertRowid).write(0, upload);
+  /// ```
+  #[napi]
+  pub fn write(&self, offset: u32, buffer: Buffer) -> Result<()> {
+    self.with(|blob| {
+      let offset = offset as usize;
+      if offset.saturating_add(buffer.len()) > blob.len() {
+        return Err(out_of_range(offset, buffer.len(), blob.len()));
+      }
+      blob.write_at(&buffer, offset).map_err(error::sqlite)
+    })
+  }
+}
+
+/// Error for bytes that do not fit in a BLOB of `size` bytes.
+fn out_of_range(offset: usize, length: usize, size: usize) -> Error {
+  Error::new(
+    Status::InvalidArg,
+    format!("Cannot access {length} bytes at offset {offset} of a BLOB of {size} bytes"),
+  )
+}
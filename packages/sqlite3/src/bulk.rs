@@ -0,0 +1,401 @@
+use super::{csv, error, sql::quote, transaction::savepoint, value};
+use napi::{
+  bindgen_prelude::{Array, Either},
+  Env, Error, JsObject, JsUnknown, Result, Status,
+};
+use napi_derive::napi;
+use rusqlite::{
+  types::{Value, ValueRef},
+  Connection,
+};
+use std::{
+  borrow::Cow,
+  collections::HashMap,
+  fs::File,
+  io::{self, BufReader, BufWriter, Write},
+};
+
+/// Savepoint wrapping an import, which also works inside a transaction.
+const SAVEPOINT: &str = "\"_import\"";
+
+/// Fields to import, or the columns receiving each field.
+type Columns = Either<Vec<String>, HashMap<String, String>>;
+
+/// Options for `Database.importJson()`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ImportOptions {
+  /// Fields to import, each into the column of the same name, or an object mapping the
+  /// name of each field to import to the column receiving it. By default, every field of
+  /// the first row is imported into the column of the same name.
+  /// @type {string[] | Record<string, string>} [columns]
+  pub columns: Option<Columns>,
+
+  /// Types the values of some columns are converted to before they are inserted, failing
+  /// on values that cannot be. `boolean` accepts `true`, `false`, `yes`, `no`, `1` and `0`.
+  /// @type {Record<string, string>} [types]
+  #[napi(ts_type = "Record<string, 'text' | 'integer' | 'real' | 'boolean'>")]
+  pub types: Option<HashMap<String, String>>,
+}
+
+/// Options for `Database.importCsv()`.
+#[napi(object)]
+#[derive(Default)]
+pub struct CsvImportOptions {
+  /// Fields to import, each into the column of the same name, or an object mapping the
+  /// name of each field to import to the column receiving it. Files without a header line
+  /// need an array, which names their fields in order.
+  /// @type {string[] | Record<string, string>} [columns]
+  pub columns: Option<Columns>,
+
+  /// Types the values of some columns are converted to, see `ImportOptions`. Other values
+  /// are inserted as text, which columns declared as `INTEGER` or `REAL` still convert.
+  /// @type {Record<string, string>} [types]
+  #[napi(ts_type = "Record<string, 'text' | 'integer' | 'real' | 'boolean'>")]
+  pub types: Option<HashMap<String, String>>,
+
+  /// @type {string} [delimiter=',']
+  pub delimiter: Option<String>,
+
+  /// Whether the first line holds the names of the fields.
+  /// @type {boolean} [header=true]
+  pub header: Option<bool>,
+
+  /// Text read as NULL, such as `NULL` or an empty string.
+  /// @type {string} [nullValue]
+  pub null_value: Option<String>,
+}
+
+/// Options for `Database.exportCsv()`.
+#[napi(object)]
+#[derive(Default)]
+pub struct CsvExportOptions {
+  /// @type {string} [delimiter=',']
+  pub delimiter: Option<String>,
+
+  /// Whether to write the names of the columns on the first line.
+  /// @type {boolean} [header=true]
+  pub header: Option<bool>,
+
+  /// Text written for NULL values.
+  /// @type {string} [nullValue='']
+  pub null_value: Option<String>,
+}
+
+/// Types accepted by the `types` option.
+#[derive(Clone, Copy)]
+enum Type {
+  Text,
+  Integer,
+  Real,
+  Boolean,
+}
+
+impl Type {
+  fn parse(column: &str, name: &str) -> Result<Self> {
+    match name {
+      "text" => Ok(Type::Text),
+      "integer" => Ok(Type::Integer),
+      "real" => Ok(Type::Real),
+      "boolean" => Ok(Type::Boolean),
+      _ => Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid type \"{name}\" for column \"{column}\", expected one of text, integer, real, boolean"),
+      )),
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Type::Text => "text",
+      Type::Integer => "integer",
+      Type::Real => "real",
+      Type::Boolean => "boolean",
+    }
+  }
+
+  /// Converts a value, `None` when it cannot be. NULL stays NULL.
+  fn convert(self, value: &Value) -> Option<Value> {
+    Some(match (self, value) {
+      (_, Value::Null) => Value::Null,
+      (_, Value::Blob(_)) => return None,
+      (Type::Text, Value::Integer(i)) => Value::Text(i.to_string()),
+      (Type::Text, Value::Real(f)) => Value::Text(f.to_string()),
+      (Type::Text, Value::Text(text)) => Value::Text(text.clone()),
+      (Type::Integer, Value::Integer(i)) => Value::Integer(*i),
+      (Type::Integer, Value::Real(f)) if f.fract() == 0.0 && f.abs() < 2f64.powi(63) => Value::Integer(*f as i64),
+      (Type::Integer, Value::Text(text)) => match text.trim().parse::<i64>() {
+        Ok(i) => Value::Integer(i),
+        Err(_) => return Type::Integer.convert(&Type::Real.convert(value)?),
+      },
+      (Type::Real, Value::Integer(i)) => Value::Real(*i as f64),
+      (Type::Real, Value::Real(f)) => Value::Real(*f),
+      (Type::Real, Value::Text(text)) => Value::Real(text.trim().parse().ok().filter(|f: &f64| f.is_finite())?),
+      (Type::Boolean, Value::Integer(i @ (0 | 1))) => Value::Integer(*i),
+      (Type::Boolean, Value::Real(f)) if *f == 0.0 || *f == 1.0 => Value::Integer(*f as i64),
+      (Type::Boolean, Value::Text(text)) => match text.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Value::Integer(1),
+        "false" | "no" | "0" => Value::Integer(0),
+        _ => return None,
+      },
+      _ => return None,
+    })
+  }
+}
+
+/// Where the imported fields go in the table.
+struct Mapping {
+  /// Index of each imported field among the fields of the source.
+  fields: Vec<usize>,
+  columns: Vec<String>,
+  types: Vec<Option<Type>>,
+}
+
+impl Mapping {
+  /// Maps the fields named `names` to columns following the `columns` and `types` options.
+  fn new(names: &[String], columns: Option<Columns>, types: Option<HashMap<String, String>>) -> Result<Self> {
+    let field = |name: &str| {
+      names
+        .iter()
+        .position(|field| field == name)
+        .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown field \"{name}\"")))
+    };
+    let pairs = match columns {
+      None => names.iter().cloned().enumerate().collect::<Vec<_>>(),
+      Some(Either::A(selected)) => selected
+        .into_iter()
+        .map(|name| Ok((field(&name)?, name)))
+        .collect::<Result<_>>()?,
+      Some(Either::B(mapping)) => mapping
+        .into_iter()
+        .map(|(name, column)| Ok((field(&name)?, column)))
+        .collect::<Result<_>>()?,
+    };
+    let (fields, columns): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+
+    let mut column_types = vec![None; columns.len()];
+    for (column, name) in types.unwrap_or_default() {
+      let Some(index) = columns.iter().position(|imported| *imported == column) else {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Column \"{column}\" of `types` is not imported"),
+        ));
+      };
+      column_types[index] = Some(Type::parse(&column, &name)?);
+    }
+
+    Ok(Mapping {
+      fields,
+      columns,
+      types: column_types,
+    })
+  }
+
+  /// The statement inserting a row into `table`.
+  fn insert(&self, table: &str) -> String {
+    let columns = self.columns.iter().map(|column| quote(column)).collect::<Vec<_>>();
+    format!(
+      "INSERT INTO {} ({}) VALUES ({})",
+      quote(table),
+      columns.join(", "),
+      vec!["?"; columns.len()].join(", ")
+    )
+  }
+
+  /// Converts the value of the `i`-th imported field to the type of its column.
+  fn convert(&self, i: usize, value: Value) -> std::result::Result<Value, String> {
+    let Some(ty) = self.types[i] else {
+      return Ok(value);
+    };
+    ty.convert(&value).ok_or_else(|| {
+      let value = match value {
+        Value::Integer(i) => i.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Text(text) => format!("\"{text}\""),
+        Value::Null | Value::Blob(_) => "a blob".to_string(),
+      };
+      format!(
+        "Cannot convert {value} to {} for column \"{}\"",
+        ty.name(),
+        self.columns[i]
+      )
+    })
+  }
+}
+
+/// Imports the records of the CSV file at `path` into `table`, returning how many were inserted.
+///
+/// # Errors
+///
+/// Returns an Error if an option is invalid, the file cannot be read or parsed, or a row
+/// cannot be inserted, in which case nothing is.
+pub fn import_csv(conn: &Connection, table: &str, path: &str, options: CsvImportOptions) -> Result<u32> {
+  let delimiter = delimiter(options.delimiter.as_deref())?;
+  let read_error = |err: io::Error| Error::new(Status::GenericFailure, format!("Cannot read \"{path}\": {err}"));
+  let file = File::open(path).map_err(read_error)?;
+  let mut reader = csv::Reader::new(BufReader::new(file), delimiter);
+
+  let header = options.header.unwrap_or(true);
+  let (names, columns) = match (header, options.columns) {
+    (true, columns) => (reader.record().map_err(read_error)?.unwrap_or_default(), columns),
+    (false, Some(Either::A(names))) => (names, None),
+    (false, _) => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Files without a header line need `columns` to name their fields",
+      ))
+    }
+  };
+  let mapping = Mapping::new(&names, columns, options.types)?;
+  if mapping.columns.is_empty() {
+    return Ok(0);
+  }
+
+  savepoint(conn, SAVEPOINT, || {
+    let mut stmt = conn.prepare(&mapping.insert(table)).map_err(error::sqlite)?;
+    let mut count = 0;
+    while let Some(record) = reader.record().map_err(read_error)? {
+      let line = reader.line();
+      if record.len() != names.len() {
+        return Err(Error::new(
+          Status::GenericFailure,
+          format!("Expected {} fields on line {line}, got {}", names.len(), record.len()),
+        ));
+      }
+
+      for (i, &field) in mapping.fields.iter().enumerate() {
+        let value = match &record[field] {
+          text if options.null_value.as_ref() == Some(text) => Value::Null,
+          text => Value::Text(text.clone()),
+        };
+        let value = mapping
+          .convert(i, value)
+          .map_err(|reason| Error::new(Status::GenericFailure, format!("{reason} on line {line}")))?;
+        stmt.raw_bind_parameter(i + 1, value).map_err(error::sqlite)?;
+      }
+      stmt.raw_execute().map_err(error::sqlite)?;
+      count += 1;
+    }
+    Ok(count)
+  })
+}
+
+/// Imports `rows` into `table`, returning how many were inserted.
+///
+/// # Errors
+///
+/// Returns an Error if an option is invalid, or a row cannot be converted or inserted,
+/// in which case nothing is.
+pub fn import_json(
+  env: &Env,
+  conn: &Connection,
+  table: &str,
+  rows: &Array,
+  options: ImportOptions,
+) -> Result<u32> {
+  if rows.len() == 0 {
+    return Ok(0);
+  }
+  let names = match &options.columns {
+    None => env.run_in_scope(|| {
+      let first = rows.get::<JsObject>(0)?.ok_or_else(|| row_error(0))?;
+      let keys = first.get_property_names()?;
+      (0..keys.get_array_length()?)
+        .map(|i| {
+          keys
+            .get_element::<JsUnknown>(i)?
+            .coerce_to_string()?
+            .into_utf8()?
+            .into_owned()
+        })
+        .collect::<Result<Vec<_>>>()
+    })?,
+    Some(Either::A(names)) => names.clone(),
+    Some(Either::B(mapping)) => mapping.keys().cloned().collect(),
+  };
+  let mapping = Mapping::new(&names, options.columns, options.types)?;
+  if mapping.columns.is_empty() {
+    return Ok(0);
+  }
+
+  savepoint(conn, SAVEPOINT, || {
+    let mut stmt = conn.prepare(&mapping.insert(table)).map_err(error::sqlite)?;
+    for index in 0..rows.len() {
+      // The values of a row are only needed while it is inserted, so a large import does
+      // not keep a handle to every row and value until it returns.
+      env.run_in_scope(|| {
+        let row = rows.get::<JsObject>(index)?.ok_or_else(|| row_error(index))?;
+        for (i, &field) in mapping.fields.iter().enumerate() {
+          let name = &names[field];
+          let value = row
+            .get_named_property::<JsUnknown>(name)
+            .and_then(value::to_value)
+            .map_err(|err| err.reason)
+            .and_then(|value| mapping.convert(i, value))
+            .map_err(|reason| {
+              Error::new(Status::InvalidArg, format!("Row {index}, field \"{name}\": {reason}"))
+            })?;
+          stmt.raw_bind_parameter(i + 1, value).map_err(error::sqlite)?;
+        }
+        stmt.raw_execute().map_err(error::sqlite)
+      })?;
+    }
+    Ok(rows.len())
+  })
+}
+
+/// Error for an index past the end of the rows, which `Array::get()` returns as `None`.
+fn row_error(index: u32) -> Error {
+  Error::new(Status::InvalidArg, format!("Row {index} is missing"))
+}
+
+/// Writes the rows returned by `sql` to the CSV file at `path`, returning how many were written.
+///
+/// # Errors
+///
+/// Returns an Error if the SQL is invalid or fails, or the file cannot be written.
+pub fn export_csv(conn: &Connection, sql: &str, path: &str, options: CsvExportOptions) -> Result<u32> {
+  let delimiter = delimiter(options.delimiter.as_deref())?;
+  let null = options.null_value.unwrap_or_default();
+  let write_error = |err: io::Error| Error::new(Status::GenericFailure, format!("Cannot write \"{path}\": {err}"));
+
+  let mut stmt = conn.prepare(sql).map_err(error::sqlite)?;
+  let mut output = BufWriter::new(File::create(path).map_err(write_error)?);
+  if options.header.unwrap_or(true) {
+    let names = stmt.column_names().into_iter().map(Cow::Borrowed);
+    csv::write_record(&mut output, names, delimiter).map_err(write_error)?;
+  }
+
+  let columns = stmt.column_count();
+  let mut rows = stmt.raw_query();
+  let mut count = 0;
+  while let Some(row) = rows.next().map_err(error::sqlite)? {
+    let fields = (0..columns).map(|i| match row.get_ref_unwrap(i) {
+      ValueRef::Null => Cow::Borrowed(null.as_str()),
+      ValueRef::Integer(i) => Cow::Owned(i.to_string()),
+      ValueRef::Real(f) => Cow::Owned(f.to_string()),
+      ValueRef::Text(text) => String::from_utf8_lossy(text),
+      // BLOBs are written in hexadecimal, like `hex()` returns them.
+      ValueRef::Blob(blob) => Cow::Owned(blob.iter().map(|byte| format!("{byte:02X}")).collect()),
+    });
+    csv::write_record(&mut output, fields, delimiter).map_err(write_error)?;
+    count += 1;
+  }
+  output.flush().map_err(write_error)?;
+
+  Ok(count)
+}
+
+/// Checks that the delimiter is a single character that cannot be confused with quotes or line breaks.
+fn delimiter(delimiter: Option<&str>) -> Result<u8> {
+  match delimiter.unwrap_or(",").as_bytes() {
+    &[byte] if !matches!(byte, b'"' | b'\r' | b'\n') && byte.is_ascii() => Ok(byte),
+    _ => Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Invalid delimiter \"{}\", expected a single character",
+        delimiter.unwrap_or_default()
+      ),
+    )),
+  }
+}
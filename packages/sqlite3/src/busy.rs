@@ -0,0 +1,144 @@
+use super::{connection::Handle, error, function::Callback};
+use napi::{Env, JsFunction, Result};
+use napi_derive::napi;
+use rusqlite::{ffi, types::Value};
+use std::{
+  os::raw::{c_int, c_void},
+  thread,
+  time::Duration,
+};
+
+/// Retries before giving up when no handler decides it.
+const DEFAULT_RETRIES: u32 = 10;
+
+/// Milliseconds to wait before the first retry.
+const DEFAULT_DELAY: u32 = 10;
+
+/// Upper bound of the wait between two retries, in milliseconds.
+const DEFAULT_MAX_DELAY: u32 = 1000;
+
+/// Options for `Database.busyHandler()`.
+#[napi(object)]
+#[derive(Default)]
+pub struct BusyHandlerOptions {
+  /// Retries before failing with `SQLITE_BUSY`. Unlimited when a handler is given,
+  /// which then decides when to stop.
+  /// @type {number} [retries=10]
+  pub retries: Option<u32>,
+
+  /// Milliseconds to wait before the first retry, doubled before each following one.
+  /// @type {number} [delay=10]
+  pub delay: Option<u32>,
+
+  /// Upper bound of the wait between two retries, in milliseconds.
+  /// @type {number} [maxDelay=1000]
+  pub max_delay: Option<u32>,
+}
+
+/// Decides whether a statement that found the database locked waits and retries.
+pub struct BusyHandler {
+  callback: Option<Callback>,
+  retries: Option<u32>,
+  delay: u32,
+  max_delay: u32,
+}
+
+impl BusyHandler {
+  /// Creates a handler calling `callback`, if any, and backing off between retries.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the callback cannot be referenced.
+  pub fn new(env: &Env, callback: Option<&JsFunction>, options: BusyHandlerOptions) -> Result<Self> {
+    Ok(BusyHandler {
+      callback: callback
+        .map(|callback| Callback::new(env, callback, false))
+        .transpose()?,
+      retries: options.retries,
+      delay: options.delay.unwrap_or(DEFAULT_DELAY),
+      max_delay: options.max_delay.unwrap_or(DEFAULT_MAX_DELAY),
+    })
+  }
+
+  /// Installs the handler on the connection, replacing any handler or timeout.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the connection is closed or SQLite rejects the handler.
+  pub fn install(self, handle: &Handle) -> Result<()> {
+    let handler = Box::new(self);
+    handle.with(|conn| {
+      let data = &*handler as *const BusyHandler as *mut c_void;
+      // SAFETY: see `Handle::set_busy_handler`.
+      let code = unsafe { ffi::sqlite3_busy_handler(conn.handle(), Some(call), data) };
+      if code != ffi::SQLITE_OK {
+        return Err(error::sqlite(rusqlite::Error::SqliteFailure(
+          ffi::Error::new(code),
+          None,
+        )));
+      }
+      Ok(())
+    })?;
+    handle.set_busy_handler(Some(handler));
+    Ok(())
+  }
+
+  /// Whether to retry after the statement found the database locked `count` times,
+  /// waiting before returning `true`.
+  fn wait(&self, count: u32) -> bool {
+    // Async queries cannot call the handler, and only rely on the retries.
+    let callback = self.callback.as_ref().filter(|callback| callback.is_callable());
+    let retries = match callback {
+      Some(_) => self.retries,
+      None => Some(self.retries.unwrap_or(DEFAULT_RETRIES)),
+    };
+    if retries.is_some_and(|retries| count >= retries) {
+      return false;
+    }
+    if let Some(callback) = callback {
+      // A handler that throws stops waiting, see `error::keep`.
+      let keep_waiting = callback
+        .argument(Value::Integer(count.into()))
+        .and_then(|retries| callback.call(&[retries]))
+        .and_then(|result| result.coerce_to_bool()?.get_value());
+      match keep_waiting {
+        Ok(true) => {}
+        Ok(false) => return false,
+        Err(err) => {
+          error::keep(err);
+          return false;
+        }
+      }
+    }
+
+    thread::sleep(self.delay(count));
+    true
+  }
+
+  /// The wait before the next retry, doubling each time up to the maximum.
+  fn delay(&self, count: u32) -> Duration {
+    let delay = u64::from(self.delay) << count.min(32);
+    Duration::from_millis(delay.min(self.max_delay.into()))
+  }
+}
+
+/// Called by SQLite with the handler given to `sqlite3_busy_handler`.
+unsafe extern "C" fn call(data: *mut c_void, count: c_int) -> c_int {
+  let handler = unsafe { &*(data as *const BusyHandler) };
+  handler.wait(count.max(0) as u32).into()
+}
+
+/// Waits up to `ms` milliseconds for a locked database, replacing any busy handler.
+///
+/// # Errors
+///
+/// Returns an Error if the connection is closed.
+pub fn timeout(handle: &Handle, ms: u32) -> Result<()> {
+  handle.with(|conn| {
+    conn
+      .busy_timeout(Duration::from_millis(ms.into()))
+      .map_err(error::sqlite)
+  })?;
+  handle.set_busy_handler(None);
+  Ok(())
+}
@@ -0,0 +1,160 @@
+use super::error;
+use napi::Result;
+use napi_derive::napi;
+use parking_lot::Mutex;
+use rusqlite::{ffi, CachedStatement, Connection};
+use std::{collections::HashMap, ffi::CStr, iter, os::raw::c_void, ptr};
+
+/// Statements rusqlite keeps per connection until told otherwise.
+const DEFAULT_CAPACITY: usize = 16;
+
+/// Name of the cache state attached to each connection as SQLite client data.
+const CLIENT_DATA: &CStr = c"sqlite3.statement_cache";
+
+/// Usage of the prepared statement cache of a connection.
+#[napi(object)]
+pub struct StatementCacheStats {
+  /// @type {number} hits - Statements reused from the cache instead of being compiled.
+  pub hits: i64,
+
+  /// @type {number} misses - Statements compiled because the cache did not hold them.
+  pub misses: i64,
+
+  /// @type {number} evictions - Statements finalized to make room for others, or because
+  /// the capacity went down.
+  pub evictions: i64,
+
+  /// @type {number} size - Statements currently in the cache.
+  pub size: u32,
+
+  /// @type {number} capacity - Most statements the cache holds.
+  pub capacity: u32,
+}
+
+/// What rusqlite's statement cache does not report about itself.
+struct State {
+  capacity: usize,
+  hits: i64,
+  misses: i64,
+  /// Statements finalized by [`flush`], which are not evictions.
+  flushed: i64,
+  /// Statements compiled for a statement handle that it did not run yet, by SQL.
+  fresh: HashMap<String, usize>,
+}
+
+/// Returns the cache state of the connection, attaching a new one the first time.
+/// SQLite frees it when the connection closes.
+fn state(conn: &Connection) -> &Mutex<State> {
+  unsafe extern "C" fn free(data: *mut c_void) {
+    drop(unsafe { Box::from_raw(data as *mut Mutex<State>) });
+  }
+
+  // SAFETY: the client data is only set here, and lives as long as the connection.
+  unsafe {
+    let db = conn.handle();
+    let mut data = ffi::sqlite3_get_clientdata(db, CLIENT_DATA.as_ptr()) as *const Mutex<State>;
+    if data.is_null() {
+      let state = Box::into_raw(Box::new(Mutex::new(State {
+        capacity: DEFAULT_CAPACITY,
+        hits: 0,
+        misses: 0,
+        flushed: 0,
+        fresh: HashMap::new(),
+      })));
+      ffi::sqlite3_set_clientdata(db, CLIENT_DATA.as_ptr(), state as *mut c_void, Some(free));
+      data = state;
+    }
+    &*data
+  }
+}
+
+/// Returns the statements SQLite holds for the connection, cached or in use.
+fn statements(conn: &Connection) -> impl Iterator<Item = *mut ffi::sqlite3_stmt> + '_ {
+  let next = |stmt| Some(unsafe { ffi::sqlite3_next_stmt(conn.handle(), stmt) }).filter(|next| !next.is_null());
+  iter::successors(next(ptr::null_mut()), move |&stmt| next(stmt))
+}
+
+/// Returns the statement compiled from `sql`, reusing the one in the connection's cache if any.
+///
+/// # Errors
+///
+/// Returns an Error if the statement is not cached and the SQL is invalid.
+pub fn prepare<'conn>(conn: &'conn Connection, sql: &str) -> Result<CachedStatement<'conn>> {
+  lookup(conn, sql, false)
+}
+
+/// Returns the statement compiled from `sql` for a new statement handle, like [`prepare`].
+/// The handle gives it back to the cache right away, so taking it again the first time the
+/// handle runs is not a hit.
+///
+/// # Errors
+///
+/// Returns an Error if the statement is not cached and the SQL is invalid.
+pub fn compile<'conn>(conn: &'conn Connection, sql: &str) -> Result<CachedStatement<'conn>> {
+  lookup(conn, sql, true)
+}
+
+fn lookup<'conn>(conn: &'conn Connection, sql: &str, compile: bool) -> Result<CachedStatement<'conn>> {
+  let before = statements(conn).count();
+  let stmt = conn.prepare_cached(sql).map_err(error::sqlite)?;
+  // A hit takes the statement out of the cache, and a miss compiles a new one.
+  let compiled = statements(conn).count() > before;
+
+  let mut state = state(conn).lock();
+  // rusqlite caches statements by their trimmed SQL.
+  let key = sql.trim();
+  let returned = !compile
+    && match state.fresh.get_mut(key) {
+      Some(1) => state.fresh.remove(key).is_some(),
+      Some(count) => {
+        *count -= 1;
+        true
+      }
+      None => false,
+    };
+  if compile {
+    *state.fresh.entry(key.to_string()).or_default() += 1;
+  }
+
+  if compiled {
+    state.misses += 1;
+  } else if !returned {
+    state.hits += 1;
+  }
+  Ok(stmt)
+}
+
+/// Changes how many statements the connection keeps, finalizing the least recently used ones.
+pub fn set_capacity(conn: &Connection, capacity: usize) {
+  conn.set_prepared_statement_cache_capacity(capacity);
+  state(conn).lock().capacity = capacity;
+}
+
+/// Finalizes every cached statement, so the next uses compile them again.
+pub fn flush(conn: &Connection) {
+  let before = statements(conn).count();
+  conn.flush_prepared_statement_cache();
+  state(conn).lock().flushed += (before - statements(conn).count()) as i64;
+}
+
+/// Returns the usage of the connection's cache since it was opened.
+pub fn stats(conn: &Connection) -> StatementCacheStats {
+  // Statements are reset when they go back to the cache. Between calls, the only other ones
+  // belong to iterators, which run them from their first row until they finish.
+  let (mut size, mut live) = (0, 0);
+  for stmt in statements(conn) {
+    live += 1;
+    if unsafe { ffi::sqlite3_stmt_busy(stmt) } == 0 {
+      size += 1;
+    }
+  }
+  let state = state(conn).lock();
+  StatementCacheStats {
+    hits: state.hits,
+    misses: state.misses,
+    // Every statement the cache compiled is still alive, was flushed or was evicted.
+    evictions: (state.misses - live - state.flushed).max(0),
+    size: size as u32,
+    capacity: state.capacity as u32,
+  }
+}
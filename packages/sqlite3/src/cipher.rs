@@ -0,0 +1,963 @@
+use super::{error, pragma};
+use aes::{
+  cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit, KeyIvInit, StreamCipher, StreamCipherSeek},
+  Aes256,
+};
+use aes_gcm::{aead::AeadInPlace, Aes256Gcm};
+use ctr::Ctr128BE;
+use napi::{Error, Result, Status};
+use parking_lot::Mutex;
+use rusqlite::{ffi, types::Value, Connection, OpenFlags};
+use std::{
+  ffi::{c_char, c_int, c_void, CString},
+  mem, ptr, slice,
+  sync::{
+    atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+    OnceLock,
+  },
+};
+
+/// A 256-bit AES key.
+pub type Key = [u8; 32];
+
+/// Random IV stored at the start of a file encrypted as a stream, so no two files share a keystream.
+type Salt = [u8; 16];
+
+/// Bytes of the random nonce sealing a page.
+const NONCE: usize = 12;
+
+/// Bytes reserved at the end of every page for its nonce and authentication tag.
+const RESERVE: usize = NONCE + 16;
+
+/// Bytes taken by the salt before the content of a stream file.
+const HEADER: i64 = mem::size_of::<Salt>() as i64;
+
+/// Bytes of the WAL header, and of the header before each of its frames.
+const WAL_HEADER: i64 = 32;
+const FRAME_HEADER: i64 = 24;
+
+/// File control opcode handling a [`Request`], outside the range used by SQLite.
+const FCNTL_REKEY: c_int = 0x7265_6b79;
+
+/// Device capabilities that no longer hold once the content is shifted by the salt.
+const ATOMIC_WRITES: c_int = ffi::SQLITE_IOCAP_ATOMIC
+  | ffi::SQLITE_IOCAP_ATOMIC512
+  | ffi::SQLITE_IOCAP_ATOMIC1K
+  | ffi::SQLITE_IOCAP_ATOMIC2K
+  | ffi::SQLITE_IOCAP_ATOMIC4K
+  | ffi::SQLITE_IOCAP_ATOMIC8K
+  | ffi::SQLITE_IOCAP_ATOMIC16K
+  | ffi::SQLITE_IOCAP_ATOMIC32K
+  | ffi::SQLITE_IOCAP_ATOMIC64K
+  | ffi::SQLITE_IOCAP_BATCH_ATOMIC;
+
+/// The VFS wrapped by ours, which does the actual I/O.
+static INNER: AtomicPtr<ffi::sqlite3_vfs> = AtomicPtr::new(ptr::null_mut());
+
+/// VFS released by their connection, ready to hold the key of another one.
+static RELEASED: Mutex<Vec<Released>> = Mutex::new(Vec::new());
+
+/// Checks that a key is 32 bytes long.
+///
+/// # Errors
+///
+/// Returns an Error if the key has another length.
+pub fn parse_key(key: &[u8]) -> Result<Key> {
+  key
+    .try_into()
+    .map_err(|_| Error::new(Status::InvalidArg, "Invalid key length. Must be 32 bytes."))
+}
+
+/// Opens a database whose pages, journal and WAL are sealed with AES-256-GCM.
+///
+/// Every file of the connection goes through a VFS holding its key, from which the key of
+/// each kind of file is derived.
+///
+/// # Errors
+///
+/// Returns an Error if the database cannot be opened or the key does not decrypt it.
+pub fn open(name: &str, flags: OpenFlags, key: Key) -> Result<Connection> {
+  let vfs = unsafe { KeyedVfs::acquire(key)? };
+  let conn = unsafe {
+    let conn = Connection::open_with_flags_and_vfs(name, flags, (*vfs).name.to_str().unwrap_or_default());
+    // From now on the main database file keeps the VFS, if it was opened.
+    KeyedVfs::release(vfs);
+    conn.map_err(error::sqlite)?
+  };
+
+  // The nonce and tag of each page live in bytes SQLite leaves unused. This only applies
+  // to new databases, existing ones keep the reserve of their header.
+  let mut reserve = RESERVE as c_int;
+  unsafe {
+    ffi::sqlite3_file_control(
+      conn.handle(),
+      c"main".as_ptr(),
+      ffi::SQLITE_FCNTL_RESERVE_BYTES,
+      ptr::from_mut(&mut reserve).cast(),
+    );
+  }
+  // Temporary tables and indexes stay in memory rather than in files encrypted as a stream.
+  pragma::set(&conn, "temp_store", &Value::Text("MEMORY".to_string()))?;
+
+  // A wrong key fails to open the first page, which SQLite reports on the first query.
+  conn
+    .query_row("SELECT count(*) FROM sqlite_schema", [], |_| Ok(()))
+    .map_err(error::sqlite)?;
+  Ok(conn)
+}
+
+/// Re-encrypts the database with a new key.
+///
+/// `VACUUM` rewrites every page in a single transaction while the VFS writes them with the new key,
+/// so a failure rolls back to the old key. Its journal keeps the key derived from the old one,
+/// so a crash is rolled back by opening the database with the old key. WAL databases go through
+/// a rollback journal meanwhile.
+///
+/// # Errors
+///
+/// Returns an Error if the database is not encrypted, a transaction is active, another
+/// connection uses the database in WAL mode or the database cannot be rewritten.
+pub fn rekey(conn: &Connection, key: &[u8]) -> Result<()> {
+  let key = parse_key(key)?;
+  if !conn.is_autocommit() {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Cannot change the key inside a transaction",
+    ));
+  }
+
+  control(conn, &mut Request::Start(key))?;
+  let result = vacuum(conn);
+  control(
+    conn,
+    &mut if result.is_ok() {
+      Request::Commit
+    } else {
+      Request::Revert
+    },
+  )?;
+  result
+}
+
+/// Rewrites every page of the database, outside of WAL mode.
+fn vacuum(conn: &Connection) -> Result<()> {
+  let journal_mode =
+    |sql: &str| -> Result<String> { conn.query_row(sql, [], |row| row.get(0)).map_err(error::sqlite) };
+
+  let wal = journal_mode("PRAGMA journal_mode")?.eq_ignore_ascii_case("wal");
+  if wal {
+    pragma::set(conn, "wal_checkpoint", &Value::Text("TRUNCATE".to_string()))?;
+    if !journal_mode("PRAGMA journal_mode = DELETE")?.eq_ignore_ascii_case("delete") {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Cannot change the key while other connections use the database",
+      ));
+    }
+  }
+
+  let result = conn.execute_batch("VACUUM").map_err(error::sqlite);
+  if wal {
+    journal_mode("PRAGMA journal_mode = WAL")?;
+  }
+  result
+}
+
+/// Sends a request to the main database file.
+fn control(conn: &Connection, request: &mut Request) -> Result<()> {
+  let rc = unsafe {
+    ffi::sqlite3_file_control(
+      conn.handle(),
+      c"main".as_ptr(),
+      FCNTL_REKEY,
+      ptr::from_mut(request).cast(),
+    )
+  };
+  match rc {
+    ffi::SQLITE_OK => Ok(()),
+    ffi::SQLITE_NOTFOUND => Err(Error::new(
+      Status::GenericFailure,
+      "The database is not encrypted, open it with a key",
+    )),
+    rc => Err(error::sqlite(rusqlite::Error::SqliteFailure(ffi::Error::new(rc), None))),
+  }
+}
+
+/// What a key is derived for, so that no two kinds of file share one.
+#[derive(Clone, Copy)]
+enum Purpose {
+  Database = 1,
+  Journal = 2,
+  Wal = 3,
+  /// Temporary files and super-journals.
+  Stream = 4,
+}
+
+/// Derives the key of a kind of file from the database key, with AES as a PRF.
+fn derive(key: &Key, purpose: Purpose) -> Key {
+  let aes = <Aes256 as KeyInit>::new(GenericArray::from_slice(key));
+  let mut derived = Key::default();
+  for (i, block) in derived.chunks_mut(16).enumerate() {
+    block[0] = purpose as u8;
+    block[1] = i as u8;
+    aes.encrypt_block(GenericArray::from_mut_slice(block));
+  }
+  derived
+}
+
+/// Returns the AEAD sealing the pages of a kind of file.
+fn aead(key: &Key, purpose: Purpose) -> Aes256Gcm {
+  <Aes256Gcm as KeyInit>::new(GenericArray::from_slice(&derive(key, purpose)))
+}
+
+/// Encrypts a page written at `offset`, storing a fresh nonce and the tag in its reserved bytes.
+fn seal(aead: &Aes256Gcm, offset: i64, page: &mut [u8]) -> bool {
+  let (data, reserve) = page.split_at_mut(page.len() - RESERVE);
+  let (nonce, tag) = reserve.split_at_mut(NONCE);
+  unsafe { ffi::sqlite3_randomness(NONCE as c_int, nonce.as_mut_ptr().cast()) };
+  match aead.encrypt_in_place_detached(GenericArray::from_slice(nonce), &offset.to_le_bytes(), data) {
+    Ok(sealed) => {
+      tag.copy_from_slice(sealed.as_slice());
+      true
+    }
+    Err(_) => false,
+  }
+}
+
+/// Decrypts a page read at `offset` and zeroes its reserved bytes, if its tag matches.
+/// The page is left untouched otherwise.
+fn unseal(aead: &Aes256Gcm, offset: i64, page: &mut [u8]) -> bool {
+  let split = page.len() - RESERVE;
+  let (nonce, tag) = page[split..].split_at(NONCE);
+  let mut data = page[..split].to_vec();
+  let opened = aead.decrypt_in_place_detached(
+    GenericArray::from_slice(nonce),
+    &offset.to_le_bytes(),
+    &mut data,
+    GenericArray::from_slice(tag),
+  );
+  if opened.is_err() {
+    return false;
+  }
+  page[..split].copy_from_slice(&data);
+  page[split..].fill(0);
+  true
+}
+
+/// Returns whether `size` is a valid page size.
+fn is_page_size(size: i64) -> bool {
+  (512..=65536).contains(&size) && size.count_ones() == 1
+}
+
+/// A VFS holding the key of a single connection, which every file it opens derives its key from.
+///
+/// SQLite can still reach the VFS of a connection after closing its main database, so it is
+/// never freed: once released, it is unregistered and reused by the next connection.
+#[repr(C)]
+struct KeyedVfs {
+  base: ffi::sqlite3_vfs,
+  name: CString,
+  key: Mutex<Key>,
+  /// Holders of the VFS: [`open`] while it runs and the main database file while it is open.
+  holders: AtomicUsize,
+  /// Whether the main database was opened, as attached databases cannot be given a key.
+  main: AtomicBool,
+}
+
+/// A released [`KeyedVfs`], only touched again once taken out of [`RELEASED`].
+struct Released(*mut KeyedVfs);
+
+unsafe impl Send for Released {}
+
+impl KeyedVfs {
+  /// Registers a VFS holding `key`, held by the caller.
+  unsafe fn acquire(key: Key) -> Result<*mut KeyedVfs> {
+    static INIT: OnceLock<bool> = OnceLock::new();
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let found = *INIT.get_or_init(|| {
+      let inner = ffi::sqlite3_vfs_find(ptr::null());
+      INNER.store(inner, Ordering::Release);
+      !inner.is_null()
+    });
+    if !found {
+      return Err(Error::new(Status::GenericFailure, "Cannot register the encryption VFS"));
+    }
+
+    let vfs = match RELEASED.lock().pop() {
+      Some(Released(vfs)) => vfs,
+      None => {
+        let inner = INNER.load(Ordering::Acquire);
+        let name =
+          CString::new(format!("aes256gcm-{}", COUNT.fetch_add(1, Ordering::Relaxed))).unwrap_or_default();
+        // Every other method comes from the default VFS, along with the `pAppData` they rely on.
+        Box::into_raw(Box::new(KeyedVfs {
+          base: ffi::sqlite3_vfs {
+            szOsFile: (mem::size_of::<File>() + (*inner).szOsFile as usize) as c_int,
+            pNext: ptr::null_mut(),
+            zName: name.as_ptr(),
+            xOpen: Some(open_file),
+            ..*inner
+          },
+          name,
+          key: Mutex::new(Key::default()),
+          holders: AtomicUsize::new(0),
+          main: AtomicBool::new(false),
+        }))
+      }
+    };
+
+    *(*vfs).key.lock() = key;
+    (*vfs).holders.store(1, Ordering::Release);
+    (*vfs).main.store(false, Ordering::Release);
+    if ffi::sqlite3_vfs_register(ptr::addr_of_mut!((*vfs).base), 0) != ffi::SQLITE_OK {
+      KeyedVfs::release(vfs);
+      return Err(Error::new(Status::GenericFailure, "Cannot register the encryption VFS"));
+    }
+    Ok(vfs)
+  }
+
+  /// Takes another hold on the VFS.
+  unsafe fn retain(vfs: *mut KeyedVfs) {
+    (*vfs).holders.fetch_add(1, Ordering::AcqRel);
+  }
+
+  /// Drops a hold on the VFS, releasing it after the last one.
+  unsafe fn release(vfs: *mut KeyedVfs) {
+    if (*vfs).holders.fetch_sub(1, Ordering::AcqRel) == 1 {
+      ffi::sqlite3_vfs_unregister(ptr::addr_of_mut!((*vfs).base));
+      *(*vfs).key.lock() = Key::default();
+      RELEASED.lock().push(Released(vfs));
+    }
+  }
+}
+
+/// Changes to the key of a main database file, sent through [`FCNTL_REKEY`].
+enum Request {
+  /// Writes pages with the new key from now on.
+  Start(Key),
+  /// Keeps the new key once every page was rewritten.
+  Commit,
+  /// Rewrites the pages written so far with the old key.
+  Revert,
+}
+
+/// Where the pages of a file are.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Layout {
+  /// One page after another.
+  Database,
+  /// Each page between its number and a checksum, after sector-aligned headers.
+  Journal,
+  /// Each page after the header of its frame, past the WAL header.
+  Wal,
+}
+
+/// A file whose pages are sealed one by one with AES-256-GCM.
+struct Pages {
+  layout: Layout,
+  key: Key,
+  aead: Aes256Gcm,
+  /// Size of the pages, once known.
+  page_size: Option<i64>,
+  /// New key of a main database, while pages are rewritten with it.
+  rekey: Option<(Key, Aes256Gcm)>,
+  /// VFS of a main database, whose key follows the key changes.
+  vfs: *mut KeyedVfs,
+  /// Name of the rollback journal of a main database.
+  journal: *const c_char,
+}
+
+impl Pages {
+  /// Returns the offset and size of the pages overlapping `len` bytes at `offset`.
+  unsafe fn overlapping(
+    &mut self,
+    file: *mut ffi::sqlite3_file,
+    offset: i64,
+    len: i64,
+  ) -> std::result::Result<Vec<(i64, i64)>, c_int> {
+    let end = offset + len;
+    match self.layout {
+      Layout::Database => {
+        let Some(size) = self.database_page_size(file)? else {
+          return Ok(Vec::new());
+        };
+        Ok(
+          (offset / size..(end + size - 1) / size)
+            .map(|page| (page * size, size))
+            .collect(),
+        )
+      }
+      // Headers are sector-aligned and page numbers take 4 bytes, so pages never start on 8 bytes.
+      Layout::Journal if offset % 8 == 4 && is_page_size(len) => Ok(vec![(offset, len)]),
+      Layout::Journal => Ok(Vec::new()),
+      Layout::Wal => {
+        let Some(size) = self.wal_page_size(file)? else {
+          return Ok(Vec::new());
+        };
+        let frame = FRAME_HEADER + size;
+        let first = (offset - WAL_HEADER).max(0) / frame;
+        Ok(
+          (first..)
+            .map(|index| (WAL_HEADER + index * frame + FRAME_HEADER, size))
+            .take_while(|&(start, _)| start < end)
+            .filter(|&(start, size)| start + size > offset)
+            .collect(),
+        )
+      }
+    }
+  }
+
+  /// Returns the page size of a main database, finding which size opens its first page.
+  unsafe fn database_page_size(
+    &mut self,
+    file: *mut ffi::sqlite3_file,
+  ) -> std::result::Result<Option<i64>, c_int> {
+    if self.page_size.is_some() {
+      return Ok(self.page_size);
+    }
+    let (inner, methods) = inner(file);
+    let mut len = 0;
+    let rc = methods.xFileSize.unwrap()(inner, &mut len);
+    if rc != ffi::SQLITE_OK {
+      return Err(rc);
+    }
+    if len == 0 {
+      return Ok(None);
+    }
+
+    let mut size = 512;
+    while size <= len.min(65536) {
+      let mut page = vec![0; size as usize];
+      let rc = methods.xRead.unwrap()(inner, page.as_mut_ptr().cast(), size as c_int, 0);
+      if rc != ffi::SQLITE_OK {
+        return Err(rc);
+      }
+      if self.unseal(0, &mut page) {
+        self.page_size = Some(size);
+        return Ok(self.page_size);
+      }
+      size *= 2;
+    }
+    Err(ffi::SQLITE_NOTADB)
+  }
+
+  /// Returns whether the rollback journal of a main database exists, and may still have
+  /// to be played back.
+  unsafe fn has_journal(&self) -> bool {
+    if self.journal.is_null() {
+      return false;
+    }
+    let inner_vfs = INNER.load(Ordering::Acquire);
+    let mut exists = 0;
+    let rc = (*inner_vfs).xAccess.unwrap()(inner_vfs, self.journal, ffi::SQLITE_ACCESS_EXISTS, &mut exists);
+    rc == ffi::SQLITE_OK && exists != 0
+  }
+
+  /// Returns the page size of a WAL, written in its header.
+  unsafe fn wal_page_size(&mut self, file: *mut ffi::sqlite3_file) -> std::result::Result<Option<i64>, c_int> {
+    if self.page_size.is_some() {
+      return Ok(self.page_size);
+    }
+    let (inner, methods) = inner(file);
+    let mut header = [0; WAL_HEADER as usize];
+    match methods.xRead.unwrap()(inner, header.as_mut_ptr().cast(), WAL_HEADER as c_int, 0) {
+      ffi::SQLITE_OK => {
+        self.page_size = wal_header_page_size(&header);
+        Ok(self.page_size)
+      }
+      ffi::SQLITE_IOERR_SHORT_READ => Ok(None),
+      rc => Err(rc),
+    }
+  }
+
+  /// Encrypts a page with the key pages are currently written with.
+  fn seal(&self, offset: i64, page: &mut [u8]) -> bool {
+    seal(self.rekey.as_ref().map_or(&self.aead, |(_, aead)| aead), offset, page)
+  }
+
+  /// Decrypts a page with whichever key it was written with.
+  fn unseal(&self, offset: i64, page: &mut [u8]) -> bool {
+    let mut aeads = self.rekey.iter().map(|(_, aead)| aead).chain([&self.aead]);
+    aeads.any(|aead| unseal(aead, offset, page))
+  }
+
+  /// Error for a page whose tag does not match.
+  fn forged(&self, offset: i64) -> c_int {
+    if self.layout == Layout::Database && offset == 0 {
+      ffi::SQLITE_NOTADB
+    } else {
+      ffi::SQLITE_CORRUPT
+    }
+  }
+}
+
+/// Reads the page size from a WAL header, where 65536 is stored as 1.
+fn wal_header_page_size(header: &[u8]) -> Option<i64> {
+  let size = i64::from(u32::from_be_bytes(header[8..12].try_into().ok()?));
+  let size = (size & 0xfe00) + ((size & 1) << 16);
+  is_page_size(size).then_some(size)
+}
+
+/// A file encrypted as a stream with AES-256-CTR, after a random salt.
+struct Stream {
+  key: Key,
+  salt: Option<Salt>,
+}
+
+impl Stream {
+  /// Encrypts or decrypts content at `offset`.
+  fn apply(&self, salt: &Salt, offset: i64, data: &mut [u8]) {
+    let mut cipher = Ctr128BE::<Aes256>::new(GenericArray::from_slice(&self.key), GenericArray::from_slice(salt));
+    cipher.seek(offset as u64);
+    cipher.apply_keystream(data);
+  }
+}
+
+/// Encryption state of an open file.
+enum Cipher {
+  /// A main database, its journal or its WAL, boxed as the AEADs make it much larger.
+  Pages(Box<Pages>),
+  /// A temporary file or a super-journal, which SQLite reads and writes at any offset.
+  Stream(Stream),
+}
+
+/// An open file: the state of the cipher, followed in memory by the file of the inner VFS.
+#[repr(C)]
+struct File {
+  base: ffi::sqlite3_file,
+  cipher: *mut Cipher,
+}
+
+/// Methods of encrypted files.
+static METHODS: ffi::sqlite3_io_methods = ffi::sqlite3_io_methods {
+  iVersion: 3,
+  xClose: Some(close),
+  xRead: Some(read),
+  xWrite: Some(write),
+  xTruncate: Some(truncate),
+  xSync: Some(sync),
+  xFileSize: Some(file_size),
+  xLock: Some(lock),
+  xUnlock: Some(unlock),
+  xCheckReservedLock: Some(check_reserved_lock),
+  xFileControl: Some(file_control),
+  xSectorSize: Some(sector_size),
+  xDeviceCharacteristics: Some(device_characteristics),
+  xShmMap: Some(shm_map),
+  xShmLock: Some(shm_lock),
+  xShmBarrier: Some(shm_barrier),
+  xShmUnmap: Some(shm_unmap),
+  xFetch: Some(fetch),
+  xUnfetch: Some(unfetch),
+};
+
+/// Returns the file of the inner VFS and its methods.
+unsafe fn inner<'a>(file: *mut ffi::sqlite3_file) -> (*mut ffi::sqlite3_file, &'a ffi::sqlite3_io_methods) {
+  let inner = file.cast::<File>().add(1).cast::<ffi::sqlite3_file>();
+  (inner, &*(*inner).pMethods)
+}
+
+unsafe fn cipher<'a>(file: *mut ffi::sqlite3_file) -> &'a mut Cipher {
+  &mut *(*file.cast::<File>()).cipher
+}
+
+/// Returns the salt of a stream file, writing a new one when the file is empty.
+unsafe fn salt(file: *mut ffi::sqlite3_file, stream: &mut Stream) -> std::result::Result<Salt, c_int> {
+  if let Some(salt) = stream.salt {
+    return Ok(salt);
+  }
+
+  let mut salt = Salt::default();
+  ffi::sqlite3_randomness(HEADER as c_int, salt.as_mut_ptr().cast());
+  let (inner, methods) = inner(file);
+  match methods.xWrite.unwrap()(inner, salt.as_ptr().cast(), HEADER as c_int, 0) {
+    ffi::SQLITE_OK => {
+      stream.salt = Some(salt);
+      Ok(salt)
+    }
+    rc => Err(rc),
+  }
+}
+
+unsafe extern "C" fn open_file(
+  vfs: *mut ffi::sqlite3_vfs,
+  name: ffi::sqlite3_filename,
+  file: *mut ffi::sqlite3_file,
+  flags: c_int,
+  out_flags: *mut c_int,
+) -> c_int {
+  (*file).pMethods = ptr::null();
+
+  let vfs = vfs.cast::<KeyedVfs>();
+  let key = *(*vfs).key.lock();
+  let main = flags & ffi::SQLITE_OPEN_MAIN_DB != 0;
+  // Attached databases and `VACUUM INTO` cannot be given a key.
+  if main && (*vfs).main.swap(true, Ordering::AcqRel) {
+    return ffi::SQLITE_CANTOPEN;
+  }
+
+  let journal = if main && !name.is_null() {
+    ffi::sqlite3_filename_journal(name)
+  } else {
+    ptr::null()
+  };
+  let pages = |layout, purpose| {
+    Cipher::Pages(Box::new(Pages {
+      layout,
+      key,
+      aead: aead(&key, purpose),
+      page_size: None,
+      rekey: None,
+      vfs: if main { vfs } else { ptr::null_mut() },
+      journal,
+    }))
+  };
+  let mut cipher = if main {
+    pages(Layout::Database, Purpose::Database)
+  } else if flags & ffi::SQLITE_OPEN_MAIN_JOURNAL != 0 {
+    pages(Layout::Journal, Purpose::Journal)
+  } else if flags & ffi::SQLITE_OPEN_WAL != 0 {
+    pages(Layout::Wal, Purpose::Wal)
+  } else {
+    Cipher::Stream(Stream {
+      key: derive(&key, Purpose::Stream),
+      salt: None,
+    })
+  };
+
+  let inner_vfs = INNER.load(Ordering::Acquire);
+  let inner = file.cast::<File>().add(1).cast::<ffi::sqlite3_file>();
+  let mut rc = (*inner_vfs).xOpen.unwrap()(inner_vfs, name, inner, flags, out_flags);
+
+  if rc == ffi::SQLITE_OK {
+    if let Cipher::Stream(stream) = &mut cipher {
+      let methods = &*(*inner).pMethods;
+      let mut size = 0;
+      rc = methods.xFileSize.unwrap()(inner, &mut size);
+      if rc == ffi::SQLITE_OK && size >= HEADER {
+        let mut bytes = Salt::default();
+        rc = methods.xRead.unwrap()(inner, bytes.as_mut_ptr().cast(), HEADER as c_int, 0);
+        stream.salt = Some(bytes);
+      }
+    }
+  }
+  if rc != ffi::SQLITE_OK {
+    if !(*inner).pMethods.is_null() {
+      (*(*inner).pMethods).xClose.unwrap()(inner);
+    }
+    if main {
+      (*vfs).main.store(false, Ordering::Release);
+    }
+    return rc;
+  }
+
+  if main {
+    KeyedVfs::retain(vfs);
+  }
+  (*file.cast::<File>()).cipher = Box::into_raw(Box::new(cipher));
+  (*file).pMethods = &METHODS;
+  ffi::SQLITE_OK
+}
+
+unsafe extern "C" fn close(file: *mut ffi::sqlite3_file) -> c_int {
+  let cipher = Box::from_raw((*file.cast::<File>()).cipher);
+  let (inner, methods) = inner(file);
+  let rc = methods.xClose.unwrap()(inner);
+  if let Cipher::Pages(pages) = *cipher {
+    if !pages.vfs.is_null() {
+      KeyedVfs::release(pages.vfs);
+    }
+  }
+  rc
+}
+
+unsafe extern "C" fn read(file: *mut ffi::sqlite3_file, buf: *mut c_void, amount: c_int, offset: i64) -> c_int {
+  let (inner, methods) = inner(file);
+  let buf = slice::from_raw_parts_mut(buf.cast::<u8>(), amount as usize);
+
+  let pages = match cipher(file) {
+    Cipher::Pages(pages) => pages,
+    Cipher::Stream(stream) => {
+      let rc = methods.xRead.unwrap()(inner, buf.as_mut_ptr().cast(), amount, offset + HEADER);
+      if rc != ffi::SQLITE_OK && rc != ffi::SQLITE_IOERR_SHORT_READ {
+        return rc;
+      }
+      // Without a salt the file is empty, and a short read already zeroed the buffer.
+      let Some(salt) = stream.salt else {
+        return rc;
+      };
+
+      // Bytes past the end of the file must stay zeroed.
+      let mut len = i64::from(amount);
+      if rc == ffi::SQLITE_IOERR_SHORT_READ {
+        let mut size = 0;
+        if methods.xFileSize.unwrap()(inner, &mut size) != ffi::SQLITE_OK {
+          return ffi::SQLITE_IOERR_READ;
+        }
+        len = (size - HEADER - offset).clamp(0, len);
+      }
+      stream.apply(&salt, offset, &mut buf[..len as usize]);
+      return rc;
+    }
+  };
+
+  let rc = methods.xRead.unwrap()(inner, buf.as_mut_ptr().cast(), amount, offset);
+  if rc != ffi::SQLITE_OK && rc != ffi::SQLITE_IOERR_SHORT_READ {
+    return rc;
+  }
+  let mut size = i64::MAX;
+  if rc == ffi::SQLITE_IOERR_SHORT_READ && methods.xFileSize.unwrap()(inner, &mut size) != ffi::SQLITE_OK {
+    return ffi::SQLITE_IOERR_READ;
+  }
+
+  let end = offset + i64::from(amount);
+  let overlapping = match pages.overlapping(file, offset, i64::from(amount)) {
+    Ok(overlapping) => overlapping,
+    // A key change stopped halfway leaves the first page under the new key until SQLite plays
+    // back the journal, which is sealed with the old one. It reads the header before that, and
+    // zeros only let it fall back to the default page size.
+    Err(ffi::SQLITE_NOTADB) if pages.has_journal() => {
+      buf.fill(0);
+      return ffi::SQLITE_OK;
+    }
+    Err(rc) => return rc,
+  };
+  for &(start, page_size) in &overlapping {
+    let (from, to) = (start.max(offset), (start + page_size).min(end));
+    let out = &mut buf[(from - offset) as usize..(to - offset) as usize];
+    // A page cut short by the end of the file reads as zeros, like the missing bytes.
+    if start + page_size > size {
+      out.fill(0);
+      continue;
+    }
+
+    let mut page = if start >= offset && start + page_size <= end {
+      out.to_vec()
+    } else {
+      let mut page = vec![0; page_size as usize];
+      let rc = methods.xRead.unwrap()(inner, page.as_mut_ptr().cast(), page_size as c_int, start);
+      if rc != ffi::SQLITE_OK {
+        return rc;
+      }
+      page
+    };
+
+    if pages.unseal(start, &mut page) {
+      out.copy_from_slice(&page[(from - start) as usize..(to - start) as usize]);
+    } else if pages.layout == Layout::Wal && (start, page_size) != (offset, i64::from(amount)) {
+      // Recovery reads whole frames, and skips torn or stale ones once their checksum fails.
+      out.fill(0);
+    } else {
+      return pages.forged(start);
+    }
+  }
+  rc
+}
+
+unsafe extern "C" fn write(file: *mut ffi::sqlite3_file, buf: *const c_void, amount: c_int, offset: i64) -> c_int {
+  let (inner, methods) = inner(file);
+  let mut data = slice::from_raw_parts(buf.cast::<u8>(), amount as usize).to_vec();
+
+  let pages = match cipher(file) {
+    Cipher::Pages(pages) => pages,
+    Cipher::Stream(stream) => {
+      let salt = match salt(file, stream) {
+        Ok(salt) => salt,
+        Err(rc) => return rc,
+      };
+      stream.apply(&salt, offset, &mut data);
+      return methods.xWrite.unwrap()(inner, data.as_ptr().cast(), amount, offset + HEADER);
+    }
+  };
+
+  let len = i64::from(amount);
+  match pages.layout {
+    Layout::Database => {
+      // SQLite writes whole pages, and the first one declares the reserve holding nonces and tags.
+      if !is_page_size(len) || offset % len != 0 || (offset == 0 && usize::from(data[20]) < RESERVE) {
+        return ffi::SQLITE_IOERR_WRITE;
+      }
+      pages.page_size = Some(len);
+    }
+    Layout::Wal if offset == 0 && len >= WAL_HEADER => pages.page_size = wal_header_page_size(&data),
+    _ => {}
+  }
+
+  match pages.overlapping(file, offset, len).as_deref() {
+    Ok([]) => {}
+    Ok(&[(start, size)]) if (start, size) == (offset, len) => {
+      if !pages.seal(offset, &mut data) {
+        return ffi::SQLITE_IOERR_WRITE;
+      }
+    }
+    // Part of a page cannot be sealed on its own.
+    Ok(_) => return ffi::SQLITE_IOERR_WRITE,
+    Err(rc) => return *rc,
+  }
+  methods.xWrite.unwrap()(inner, data.as_ptr().cast(), amount, offset)
+}
+
+unsafe extern "C" fn truncate(file: *mut ffi::sqlite3_file, size: i64) -> c_int {
+  let size = match cipher(file) {
+    Cipher::Stream(stream) if size > 0 || stream.salt.is_some() => match salt(file, stream) {
+      Ok(_) => size + HEADER,
+      Err(rc) => return rc,
+    },
+    _ => size,
+  };
+  let (inner, methods) = inner(file);
+  methods.xTruncate.unwrap()(inner, size)
+}
+
+unsafe extern "C" fn sync(file: *mut ffi::sqlite3_file, flags: c_int) -> c_int {
+  let (inner, methods) = inner(file);
+  methods.xSync.unwrap()(inner, flags)
+}
+
+unsafe extern "C" fn file_size(file: *mut ffi::sqlite3_file, size: *mut i64) -> c_int {
+  let (inner, methods) = inner(file);
+  let rc = methods.xFileSize.unwrap()(inner, size);
+  if let Cipher::Stream(_) = cipher(file) {
+    *size = (*size - HEADER).max(0);
+  }
+  rc
+}
+
+unsafe extern "C" fn lock(file: *mut ffi::sqlite3_file, level: c_int) -> c_int {
+  let (inner, methods) = inner(file);
+  methods.xLock.unwrap()(inner, level)
+}
+
+unsafe extern "C" fn unlock(file: *mut ffi::sqlite3_file, level: c_int) -> c_int {
+  let (inner, methods) = inner(file);
+  methods.xUnlock.unwrap()(inner, level)
+}
+
+unsafe extern "C" fn check_reserved_lock(file: *mut ffi::sqlite3_file, out: *mut c_int) -> c_int {
+  let (inner, methods) = inner(file);
+  methods.xCheckReservedLock.unwrap()(inner, out)
+}
+
+unsafe extern "C" fn file_control(file: *mut ffi::sqlite3_file, op: c_int, arg: *mut c_void) -> c_int {
+  let (inner, methods) = inner(file);
+  match (op, cipher(file)) {
+    (FCNTL_REKEY, Cipher::Pages(pages)) if !pages.vfs.is_null() => {
+      handle_request(file, pages, &mut *arg.cast::<Request>())
+    }
+    (FCNTL_REKEY, _) => ffi::SQLITE_NOTFOUND,
+    (ffi::SQLITE_FCNTL_SIZE_HINT, Cipher::Stream(_)) => {
+      let mut hint = *arg.cast::<i64>() + HEADER;
+      methods.xFileControl.unwrap()(inner, op, ptr::from_mut(&mut hint).cast())
+    }
+    _ => methods.xFileControl.unwrap()(inner, op, arg),
+  }
+}
+
+/// Applies a [`Request`] to a main database file.
+unsafe fn handle_request(file: *mut ffi::sqlite3_file, pages: &mut Pages, request: &mut Request) -> c_int {
+  match request {
+    Request::Start(key) => {
+      pages.rekey = Some((*key, aead(key, Purpose::Database)));
+      ffi::SQLITE_OK
+    }
+    Request::Commit => {
+      if let Some((key, aead)) = pages.rekey.take() {
+        pages.key = key;
+        pages.aead = aead;
+        // Journals, WALs and temporary files opened from now on derive their key from the new one.
+        *(*pages.vfs).key.lock() = key;
+      }
+      ffi::SQLITE_OK
+    }
+    Request::Revert => {
+      let Some((_, rekey)) = pages.rekey.take() else {
+        return ffi::SQLITE_OK;
+      };
+      let Some(page_size) = pages.page_size else {
+        return ffi::SQLITE_OK;
+      };
+
+      let (inner, methods) = inner(file);
+      let mut size = 0;
+      let rc = methods.xFileSize.unwrap()(inner, &mut size);
+      if rc != ffi::SQLITE_OK {
+        return rc;
+      }
+      let mut page = vec![0; page_size as usize];
+      for offset in (0..size - size % page_size).step_by(page_size as usize) {
+        let rc = methods.xRead.unwrap()(inner, page.as_mut_ptr().cast(), page_size as c_int, offset);
+        if rc != ffi::SQLITE_OK {
+          return rc;
+        }
+        if !unseal(&rekey, offset, &mut page) {
+          continue;
+        }
+        if !seal(&pages.aead, offset, &mut page) {
+          return ffi::SQLITE_IOERR_WRITE;
+        }
+        let rc = methods.xWrite.unwrap()(inner, page.as_ptr().cast(), page_size as c_int, offset);
+        if rc != ffi::SQLITE_OK {
+          return rc;
+        }
+      }
+      ffi::SQLITE_OK
+    }
+  }
+}
+
+unsafe extern "C" fn sector_size(file: *mut ffi::sqlite3_file) -> c_int {
+  let (inner, methods) = inner(file);
+  methods.xSectorSize.unwrap()(inner)
+}
+
+unsafe extern "C" fn device_characteristics(file: *mut ffi::sqlite3_file) -> c_int {
+  let (inner, methods) = inner(file);
+  methods.xDeviceCharacteristics.unwrap()(inner) & !ATOMIC_WRITES
+}
+
+unsafe extern "C" fn shm_map(
+  file: *mut ffi::sqlite3_file,
+  page: c_int,
+  page_size: c_int,
+  extend: c_int,
+  out: *mut *mut c_void,
+) -> c_int {
+  let (inner, methods) = inner(file);
+  methods
+    .xShmMap
+    .map_or(ffi::SQLITE_IOERR, |f| f(inner, page, page_size, extend, out))
+}
+
+unsafe extern "C" fn shm_lock(file: *mut ffi::sqlite3_file, offset: c_int, n: c_int, flags: c_int) -> c_int {
+  let (inner, methods) = inner(file);
+  methods
+    .xShmLock
+    .map_or(ffi::SQLITE_IOERR, |f| f(inner, offset, n, flags))
+}
+
+unsafe extern "C" fn shm_barrier(file: *mut ffi::sqlite3_file) {
+  let (inner, methods) = inner(file);
+  if let Some(f) = methods.xShmBarrier {
+    f(inner);
+  }
+}
+
+unsafe extern "C" fn shm_unmap(file: *mut ffi::sqlite3_file, delete: c_int) -> c_int {
+  let (inner, methods) = inner(file);
+  methods.xShmUnmap.map_or(ffi::SQLITE_OK, |f| f(inner, delete))
+}
+
+/// Memory-mapped pages would bypass decryption, so SQLite always falls back to `read`.
+unsafe extern "C" fn fetch(
+  _file: *mut ffi::sqlite3_file,
+  _offset: i64,
+  _amount: c_int,
+  out: *mut *mut c_void,
+) -> c_int {
+  *out = ptr::null_mut();
+  ffi::SQLITE_OK
+}
+
+unsafe extern "C" fn unfetch(_file: *mut ffi::sqlite3_file, _offset: i64, _page: *mut c_void) -> c_int {
+  ffi::SQLITE_OK
+}
@@ -0,0 +1,77 @@
+use super::error;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use rusqlite::{ffi, Connection};
+use std::{
+  ffi::{c_char, CStr, CString},
+  ptr,
+};
+
+/// Describes a column returned by a statement.
+#[napi(object)]
+pub struct ColumnInfo {
+  /// The name of the column in the result set, including its alias.
+  /// @type {string} name
+  pub name: String,
+
+  /// The name of the column in its table, missing for expressions.
+  /// @type {string} [column]
+  pub column: Option<String>,
+
+  /// The table the column comes from, missing for expressions.
+  /// @type {string} [table]
+  pub table: Option<String>,
+
+  /// The database the table belongs to (`main`, `temp`...), missing for expressions.
+  /// @type {string} [database]
+  pub database: Option<String>,
+
+  /// The declared type of the column, missing for expressions.
+  /// @type {string} [type]
+  #[napi(js_name = "type")]
+  pub decl_type: Option<String>,
+}
+
+/// Reads the metadata of every column returned by the given SQL.
+///
+/// rusqlite does not expose the origin of a column, so the SQL is compiled again
+/// through the C API, which also reflects the current schema.
+///
+/// # Errors
+///
+/// Returns an Error if the SQL cannot be compiled.
+pub fn describe(conn: &Connection, sql: &str) -> Result<Vec<ColumnInfo>> {
+  let sql = CString::new(sql).map_err(|e| Error::new(Status::InvalidArg, e))?;
+
+  unsafe {
+    let db = conn.handle();
+    let mut stmt = ptr::null_mut();
+    let rc = ffi::sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, ptr::null_mut());
+    if rc != ffi::SQLITE_OK {
+      let message = to_string(ffi::sqlite3_errmsg(db));
+      return Err(error::sqlite(rusqlite::Error::SqliteFailure(
+        ffi::Error::new(rc),
+        message,
+      )));
+    }
+
+    let count = ffi::sqlite3_column_count(stmt);
+    let columns = (0..count)
+      .map(|i| ColumnInfo {
+        name: to_string(ffi::sqlite3_column_name(stmt, i)).unwrap_or_default(),
+        column: to_string(ffi::sqlite3_column_origin_name(stmt, i)),
+        table: to_string(ffi::sqlite3_column_table_name(stmt, i)),
+        database: to_string(ffi::sqlite3_column_database_name(stmt, i)),
+        decl_type: to_string(ffi::sqlite3_column_decltype(stmt, i)),
+      })
+      .collect();
+    ffi::sqlite3_finalize(stmt);
+
+    Ok(columns)
+  }
+}
+
+#[inline]
+unsafe fn to_string(ptr: *const c_char) -> Option<String> {
+  (!ptr.is_null()).then(|| CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
@@ -0,0 +1,178 @@
+use super::{busy::BusyHandler, error, task::Job, trace::Tracer};
+use napi::Result;
+use parking_lot::{Mutex, ReentrantMutex, ReentrantMutexGuard};
+use rusqlite::Connection;
+use std::{
+  cell::RefCell,
+  collections::VecDeque,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+};
+
+/// A guard over the connection slot, held while the connection is in use.
+pub type Guard<'a> = ReentrantMutexGuard<'a, RefCell<Option<Connection>>>;
+
+/// The async queries of a connection, which run one at a time in order.
+#[derive(Default)]
+struct Queue {
+  jobs: VecDeque<Job>,
+  /// Whether a task is running the queued queries, until none is left.
+  running: bool,
+  /// Queries whose promise has not settled yet.
+  pending: usize,
+}
+
+struct Inner {
+  conn: ReentrantMutex<RefCell<Option<Connection>>>,
+  iterators: AtomicUsize,
+  queue: Mutex<Queue>,
+  busy_handler: Mutex<Option<Box<BusyHandler>>>,
+  tracer: Mutex<Option<Box<Tracer>>>,
+}
+
+/// Shared handle to a connection, owned by a database and every statement prepared from it.
+///
+/// The lock is reentrant so that JS callbacks invoked by SQLite (functions, hooks...)
+/// can use the same connection without deadlocking.
+#[derive(Clone)]
+pub struct Handle(Arc<Inner>);
+
+impl Handle {
+  pub fn new(conn: Connection) -> Self {
+    Handle(Arc::new(Inner {
+      conn: ReentrantMutex::new(RefCell::new(Some(conn))),
+      iterators: AtomicUsize::new(0),
+      queue: Mutex::default(),
+      busy_handler: Mutex::new(None),
+      tracer: Mutex::new(None),
+    }))
+  }
+
+  /// Locks the connection slot, which may be empty if the connection was closed.
+  #[inline]
+  pub fn lock(&self) -> Guard<'_> {
+    self.0.conn.lock()
+  }
+
+  /// Runs `f` with the open connection, from the JS thread.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the connection is closed, async queries are queued on it, which
+  /// would block the event loop until they finish, or `f` fails. When a JS callback made
+  /// `f` fail, the Error is the exception it threw.
+  pub fn with<T, F>(&self, f: F) -> Result<T>
+  where
+    F: FnOnce(&Connection) -> Result<T>,
+  {
+    self.check_idle()?;
+    let guard = self.lock();
+    let conn = guard.borrow();
+    error::rethrow(f(conn.as_ref().ok_or_else(error::not_open)?))
+  }
+
+  /// Checks that no async query is queued on the connection.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error while async queries are queued.
+  pub fn check_idle(&self) -> Result<()> {
+    if self.0.queue.lock().pending > 0 {
+      return Err(error::busy());
+    }
+
+    Ok(())
+  }
+
+  /// Whether the connection is still open, without waiting for the async queries,
+  /// which keep it open until they finish.
+  pub fn is_open(&self) -> bool {
+    self.0.queue.lock().pending > 0 || self.lock().borrow().is_some()
+  }
+
+  /// Queues `job` after the async queries queued before it, returning whether no task
+  /// is running the queue, in which case the caller starts one.
+  /// The connection is busy until [`Handle::settle`] is called for the query.
+  pub fn enqueue(&self, job: Job) -> bool {
+    let mut queue = self.0.queue.lock();
+    self.acquire();
+    queue.jobs.push_back(job);
+    queue.pending += 1;
+    let idle = !queue.running;
+    queue.running = true;
+    idle
+  }
+
+  /// Marks a queued query as settled, from the JS thread once its promise is.
+  pub fn settle(&self) {
+    self.0.queue.lock().pending -= 1;
+    self.release();
+  }
+
+  /// Takes the next queued query, or marks the queue as idle when none is left.
+  pub fn dequeue(&self) -> Option<Job> {
+    let mut queue = self.0.queue.lock();
+    let job = queue.jobs.pop_front();
+    queue.running = job.is_some();
+    job
+  }
+
+  /// Closes the connection, finalizing every cached statement.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if an iterator, a backup or a session is still using the connection or SQLite refuses to close it.
+  pub fn close(&self) -> Result<()> {
+    if self.0.iterators.load(Ordering::Acquire) > 0 {
+      return Err(error::busy());
+    }
+
+    let guard = self.lock();
+    let Ok(mut slot) = guard.try_borrow_mut() else {
+      return Err(error::busy());
+    };
+    if let Some(conn) = slot.take() {
+      if let Err((conn, err)) = conn.close() {
+        *slot = Some(conn);
+        return Err(error::sqlite(err));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Keeps the busy handler installed on the connection alive, dropping the one it replaced.
+  ///
+  /// SQLite holds callbacks such as the busy handler and the tracer by raw pointer, so the handle
+  /// owns them. They are only replaced once the new one is installed, after which SQLite no
+  /// longer uses the old one.
+  pub fn set_busy_handler(&self, handler: Option<Box<BusyHandler>>) {
+    *self.0.busy_handler.lock() = handler;
+  }
+
+  /// Keeps the tracer installed on the connection alive, dropping the one it replaced, like
+  /// [`Handle::set_busy_handler`].
+  pub fn set_tracer(&self, tracer: Option<Box<Tracer>>) {
+    *self.0.tracer.lock() = tracer;
+  }
+
+  /// Registers an iterator, a backup or a session that uses the connection across calls, preventing it from closing.
+  ///
+  /// These borrow the connection for `'static`: it lives behind the handle's `Arc`, and is only
+  /// dropped by [`Handle::close`], which refuses to run until every one of them is released.
+  pub fn acquire(&self) {
+    self.0.iterators.fetch_add(1, Ordering::AcqRel);
+  }
+
+  /// Whether an iterator, a backup or an async task is using the connection.
+  pub fn is_busy(&self) -> bool {
+    self.0.iterators.load(Ordering::Acquire) > 0
+  }
+
+  /// Releases a user registered with [`Handle::acquire`].
+  pub fn release(&self) {
+    self.0.iterators.fetch_sub(1, Ordering::AcqRel);
+  }
+}
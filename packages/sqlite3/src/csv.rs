@@ -0,0 +1,122 @@
+use std::{
+  borrow::Cow,
+  io::{self, BufRead, Write},
+};
+
+/// Byte order mark some editors write at the start of UTF-8 files.
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Reads the records of a CSV file as described by RFC 4180, accepting both `\n`
+/// and `\r\n` line endings. Blank lines are skipped.
+pub struct Reader<R> {
+  input: R,
+  delimiter: u8,
+  line: usize,
+}
+
+impl<R: BufRead> Reader<R> {
+  pub fn new(input: R, delimiter: u8) -> Self {
+    Reader {
+      input,
+      delimiter,
+      line: 0,
+    }
+  }
+
+  /// Line number where the last record read ends, starting at 1.
+  pub fn line(&self) -> usize {
+    self.line
+  }
+
+  /// Reads the fields of the next record, `None` at the end of the input.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if reading fails, a quoted field is not closed or a field is not UTF-8.
+  pub fn record(&mut self) -> io::Result<Option<Vec<String>>> {
+    let mut line = Vec::new();
+    loop {
+      line.clear();
+      if self.input.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+      }
+      self.line += 1;
+      if self.line == 1 && line.starts_with(BOM) {
+        line.drain(..BOM.len());
+      }
+      if !matches!(line.as_slice(), b"\n" | b"\r\n") {
+        break;
+      }
+    }
+
+    let mut fields = Vec::new();
+    let mut field = Vec::new();
+    let mut quoted = false;
+    let mut i = 0;
+    loop {
+      let Some(&byte) = line.get(i) else {
+        if !quoted {
+          break;
+        }
+        // A quoted field goes on with the next line.
+        if self.input.read_until(b'\n', &mut line)? == 0 {
+          return Err(invalid(format!("Unterminated quoted field on line {}", self.line)));
+        }
+        self.line += 1;
+        continue;
+      };
+      i += 1;
+
+      match byte {
+        b'"' if quoted && line.get(i) == Some(&b'"') => {
+          field.push(b'"');
+          i += 1;
+        }
+        b'"' if quoted => quoted = false,
+        _ if quoted => field.push(byte),
+        b'"' if field.is_empty() => quoted = true,
+        b'\n' => break,
+        b'\r' if line.get(i) == Some(&b'\n') => break,
+        _ if byte == self.delimiter => fields.push(self.string(std::mem::take(&mut field))?),
+        _ => field.push(byte),
+      }
+    }
+    fields.push(self.string(field)?);
+
+    Ok(Some(fields))
+  }
+
+  fn string(&self, field: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(field).map_err(|_| invalid(format!("Invalid UTF-8 on line {}", self.line)))
+  }
+}
+
+/// Writes a record, quoting the fields that contain the delimiter, quotes or line breaks.
+///
+/// # Errors
+///
+/// Returns an Error if writing fails.
+pub fn write_record<'a, W, I>(output: &mut W, fields: I, delimiter: u8) -> io::Result<()>
+where
+  W: Write,
+  I: IntoIterator<Item = Cow<'a, str>>,
+{
+  for (i, field) in fields.into_iter().enumerate() {
+    if i > 0 {
+      output.write_all(&[delimiter])?;
+    }
+    if field
+      .bytes()
+      .any(|byte| matches!(byte, b'"' | b'\n' | b'\r') || byte == delimiter)
+    {
+      write!(output, "\"{}\"", field.replace('"', "\"\""))?;
+    } else {
+      output.write_all(field.as_bytes())?;
+    }
+  }
+  output.write_all(b"\n")
+}
+
+fn invalid(message: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,698 @@
+use super::{
+  aggregate::{self, AggregateOptions},
+  backup::{BackupOptions, BackupTask},
+  blob::{BlobHandle, BlobOptions},
+  bulk::{self, CsvExportOptions, CsvImportOptions, ImportOptions},
+  busy::{self, BusyHandler, BusyHandlerOptions},
+  cache::{self, StatementCacheStats},
+  cipher,
+  connection::Handle,
+  error,
+  function::{self, FunctionOptions},
+  hooks::Hooks,
+  migrate::{self, MigrateOptions, MigrateResult},
+  options::DatabaseOptions,
+  pragma::{self, PragmaOptions},
+  row::Row,
+  serialize::{self, DeserializeOptions},
+  session::{self, ApplyChangesetOptions, Session},
+  statement::Statement,
+  table::{self, TableOptions},
+  task,
+  trace::Tracer,
+  transaction, value,
+};
+use napi::{
+  bindgen_prelude::{Array, AsyncTask, BigInt, Buffer, Either, This},
+  Env, Error, JsFunction, JsObject, JsUnknown, Result, Status,
+};
+use napi_derive::napi;
+use rusqlite::{types::Value, Connection, DatabaseName, LoadExtensionGuard};
+
+/// Name of in-memory databases.
+const MEMORY: &str = ":memory:";
+
+/// A connection to a SQLite database file or an in-memory database.
+#[napi]
+pub struct Database {
+  /// @type {string} name - The path given to open the database, or `:memory:`.
+  /// @readonly
+  #[napi(readonly)]
+  pub name: String,
+
+  /// @type {string} filename - The absolute path of the database file, empty for in-memory databases.
+  /// @readonly
+  #[napi(readonly)]
+  pub filename: String,
+
+  safe_integers: bool,
+  allow_extensions: bool,
+  hooks: Hooks,
+  tracer: Tracer,
+  handle: Handle,
+}
+
+#[napi]
+impl Database {
+  /// Opens a connection to the database at the given path.
+  /// Use `:memory:` to open a private, temporary in-memory database.
+  /// @param {string} name - Path of the database file or `:memory:`.
+  /// @param {DatabaseOptions} [options]
+  ///
+  /// Example:
+  /// ```js
+  /// const db = new Database('./data.db', { journalMode: 'wal', foreignKeys: true });
+  /// ```
+  #[napi(constructor)]
+  pub fn new(name: String, options: Option<DatabaseOptions>) -> Result<Self> {
+    let options = options.unwrap_or_default();
+    let conn = options.open(&name)?;
+    Ok(Database {
+      allow_extensions: options.allow_extensions.unwrap_or(false),
+      ..Database::from_connection(name, conn)
+    })
+  }
+
+  /// Opens an in-memory database holding a copy of an image returned by `serialize()`.
+  /// @param {Buffer} buffer
+  /// @param {DeserializeOptions} [options]
+  /// @returns {Database} database
+  ///
+  /// Example:
+  /// ```js
+  /// const db = Database.deserialize(fs.readFileSync('./fixture.db'), { readonly: true });
+  /// ```
+  #[napi(factory, strict)]
+  pub fn deserialize(buffer: Buffer, options: Option<DeserializeOptions>) -> Result<Self> {
+    let conn = serialize::deserialize(&buffer, &options.unwrap_or_default())?;
+    Ok(Database::from_connection(MEMORY.to_string(), conn))
+  }
+
+  /// @type {boolean} isOpen - Whether the connection is open.
+  #[napi(getter)]
+  pub fn is_open(&self) -> bool {
+    self.handle.is_open()
+  }
+
+  /// @type {boolean} inTransaction - Whether a transaction is currently active.
+  #[napi(getter)]
+  pub fn in_transaction(&self) -> bool {
+    self.handle.with(|conn| Ok(!conn.is_autocommit())).unwrap_or(false)
+  }
+
+  /// @type {boolean} readonly - Whether the database was opened in read-only mode.
+  #[napi(getter)]
+  pub fn readonly(&self) -> Result<bool> {
+    self
+      .handle
+      .with(|conn| conn.is_readonly(DatabaseName::Main).map_err(error::sqlite))
+  }
+
+  /// Executes one or more SQL statements separated by semicolons, without returning rows.
+  /// @param {string} sql
+  /// @returns {undefined}
+  #[napi]
+  pub fn exec(&self, sql: String) -> Result<()> {
+    self.handle.with(|conn| conn.execute_batch(&sql).map_err(error::sqlite))
+  }
+
+  /// Executes one or more SQL statements on the libuv thread pool, without blocking the event loop.
+  /// Async queries run one at a time in the order they were made, and until they finish the connection
+  /// cannot be closed and its synchronous methods throw. User-defined functions cannot be called from them.
+  /// @param {string} sql
+  /// @returns {Promise<void>}
+  ///
+  /// Example:
+  /// ```js
+  /// await db.execAsync('VACUUM');
+  /// ```
+  #[napi(ts_return_type = "Promise<void>")]
+  pub fn exec_async(&self, env: Env, sql: String) -> Result<JsObject> {
+    task::query(&env, &self.handle, move |conn| {
+      conn.execute_batch(&sql).map_err(error::sqlite)
+    })
+  }
+
+  /// Applies the pending migrations in order of version, each in its own transaction,
+  /// and records them in a tracking table. Refuses to run when an applied migration changed.
+  /// Passing a lower `to` version reverts the migrations above it with their `down` scripts.
+  /// @param {MigrateOptions} options
+  /// @returns {MigrateResult} result
+  ///
+  /// Example:
+  /// ```js
+  /// // migrations/001-users.sql, migrations/002-posts.sql...
+  /// const { version, applied } = db.migrate({ directory: './migrations' });
+  /// ```
+  #[napi(strict)]
+  pub fn migrate(&self, options: MigrateOptions) -> Result<MigrateResult> {
+    self.handle.with(|conn| migrate::migrate(conn, options))
+  }
+
+  /// Runs a PRAGMA statement, setting it first when a value is given, and returns its rows.
+  /// The name may be prefixed with a schema, such as `main.` or an attached database.
+  /// @param {string} name
+  /// @param {number | string | boolean} [value]
+  /// @param {PragmaOptions} [options]
+  /// @returns {unknown} result - Every row, or only the first value when `simple` is set.
+  ///
+  /// Example:
+  /// ```js
+  /// db.pragma('cache_size', 32000);
+  /// db.pragma('journal_mode', undefined, { simple: true }); // 'wal'
+  /// db.pragma('table_info', 'users'); // [{ cid: 0, name: 'id', ... }]
+  /// ```
+  #[napi(
+    ts_args_type = "name: string, value?: number | string | boolean | null, options?: PragmaOptions",
+    ts_return_type = "unknown"
+  )]
+  pub fn pragma(
+    &self,
+    name: String,
+    value: Option<JsUnknown>,
+    options: Option<PragmaOptions>,
+  ) -> Result<Either<Vec<Row>, Option<Row>>> {
+    let value = value.map(value::to_value).transpose()?;
+    let value = value.filter(|value| *value != Value::Null);
+    self.handle.with(|conn| {
+      pragma::run(
+        conn,
+        &name,
+        value.as_ref(),
+        &options.unwrap_or_default(),
+        self.safe_integers,
+      )
+    })
+  }
+
+  /// Compiles the given SQL into a prepared statement.
+  /// @param {string} sql
+  /// @returns {Statement} statement
+  ///
+  /// Example:
+  /// ```js
+  /// const stmt = db.prepare('SELECT * FROM users WHERE id = ?');
+  /// const user = stmt.get([1]);
+  /// ```
+  #[napi]
+  pub fn prepare(&self, sql: String) -> Result<Statement> {
+    Statement::new(self.handle.clone(), sql, self.safe_integers)
+  }
+
+  /// Changes how many prepared statements the connection keeps compiled, 16 by default.
+  /// The least recently used ones are finalized when the cache is full, and `0` disables it.
+  /// @param {number} capacity
+  /// @returns {this}
+  #[napi]
+  pub fn set_statement_cache_capacity(&self, this: This, capacity: u32) -> Result<This> {
+    self.handle.with(|conn| {
+      cache::set_capacity(conn, capacity as usize);
+      Ok(())
+    })?;
+    Ok(this)
+  }
+
+  /// Returns how often statements were reused from the prepared statement cache
+  /// instead of being compiled again since the connection was opened, how many it
+  /// evicted, and how many statements the cache holds. Running a statement for the first
+  /// time reuses the one compiled by `prepare()` and is not a hit.
+  /// @returns {StatementCacheStats} stats
+  ///
+  /// Example:
+  /// ```js
+  /// const { hits, misses } = db.statementCacheStats();
+  /// metrics.gauge('sqlite.statement_cache.hit_ratio', hits / (hits + misses));
+  /// ```
+  #[napi]
+  pub fn statement_cache_stats(&self) -> Result<StatementCacheStats> {
+    self.handle.with(|conn| Ok(cache::stats(conn)))
+  }
+
+  /// Wraps a function so that it runs inside a transaction, which is committed when it returns
+  /// and rolled back when it throws. Calling it inside another transaction uses a savepoint instead.
+  /// The `deferred`, `immediate` and `exclusive` properties run it with that kind of `BEGIN`.
+  /// @param {Function} fn
+  /// @returns {Function} transaction
+  ///
+  /// Example:
+  /// ```js
+  /// const insertMany = db.transaction((users) => {
+  ///   for (const user of users) insert.run(user);
+  /// });
+  /// insertMany.immediate([['Amniel'], ['Rust']]);
+  /// ```
+  #[napi(
+    strict,
+    ts_generic_types = "F extends (...args: any[]) => any",
+    ts_args_type = "fn: F",
+    ts_return_type = "F & { deferred: F; immediate: F; exclusive: F }"
+  )]
+  pub fn transaction(&self, env: Env, callback: JsFunction) -> Result<JsFunction> {
+    transaction::create(&env, &self.handle, &callback)
+  }
+
+  /// Registers a JS function that can be called from SQL. Registering a function with the
+  /// same name and number of arguments replaces it. Its arguments follow `safeIntegers()`.
+  /// @param {string} name
+  /// @param {FunctionOptions} [options]
+  /// @param {Function} fn - Receives the SQL arguments and returns the result.
+  /// @returns {this}
+  ///
+  /// Example:
+  /// ```js
+  /// db.function('double', { deterministic: true }, (n) => n * 2);
+  /// db.prepare('SELECT double(21) AS answer').get(); // { answer: 42 }
+  /// ```
+  #[napi(
+    strict,
+    ts_args_type = "name: string, options: FunctionOptions | ((...args: any[]) => unknown), fn?: (...args: any[]) => unknown"
+  )]
+  pub fn function(
+    &self,
+    env: Env,
+    this: This,
+    name: String,
+    options: Either<JsFunction, FunctionOptions>,
+    callback: Option<JsFunction>,
+  ) -> Result<This> {
+    let (options, callback) = match (options, callback) {
+      (Either::A(callback), None) => (FunctionOptions::default(), callback),
+      (Either::B(options), Some(callback)) => (options, callback),
+      _ => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "Expected a function as the last argument",
+        ))
+      }
+    };
+
+    self
+      .handle
+      .with(|conn| function::create_scalar(&env, conn, &name, &options, &callback, self.safe_integers))?;
+    Ok(this)
+  }
+
+  /// Registers an aggregate function that can be called from SQL, which is also
+  /// a window function when `inverse` is given. Its arguments follow `safeIntegers()`.
+  /// @param {string} name
+  /// @param {AggregateOptions} options
+  /// @returns {this}
+  ///
+  /// Example:
+  /// ```js
+  /// db.aggregate('weighted_avg', {
+  ///   start: () => ({ sum: 0, weight: 0 }),
+  ///   step: (acc, value, weight) => ({ sum: acc.sum + value * weight, weight: acc.weight + weight }),
+  ///   inverse: (acc, value, weight) => ({ sum: acc.sum - value * weight, weight: acc.weight - weight }),
+  ///   result: (acc) => (acc.weight ? acc.sum / acc.weight : null),
+  /// });
+  /// db.prepare('SELECT weighted_avg(price, quantity) FROM orders').pluck().get();
+  /// ```
+  #[napi(strict)]
+  pub fn aggregate(&self, env: Env, this: This, name: String, options: AggregateOptions) -> Result<This> {
+    self
+      .handle
+      .with(|conn| aggregate::create(&env, conn, &name, options, self.safe_integers))?;
+    Ok(this)
+  }
+
+  /// Defines an eponymous virtual table whose rows are produced by a JS generator each time
+  /// a statement reads it, to query in-memory data with SQL and join it with stored tables.
+  /// The parameters are given as arguments, as in `FROM name(...)`, and follow `safeIntegers()`.
+  /// Defining a table again with the same name replaces it.
+  /// @param {string} name
+  /// @param {TableOptions} options
+  /// @returns {this}
+  ///
+  /// Example:
+  /// ```js
+  /// db.table('translations', {
+  ///   columns: ['key', 'text'],
+  ///   parameters: ['locale'],
+  ///   *rows(locale) {
+  ///     yield* Object.entries(catalogues[locale] ?? {});
+  ///   },
+  /// });
+  /// db.prepare("SELECT p.id, t.text FROM products p JOIN translations('fr') t ON t.key = p.title_key").all();
+  /// ```
+  #[napi(strict)]
+  pub fn table(&self, env: Env, this: This, name: String, options: TableOptions) -> Result<This> {
+    self
+      .handle
+      .with(|conn| table::create(&env, conn, name, options, self.safe_integers))?;
+    Ok(this)
+  }
+
+  /// Listens to changes of the database: `update` receives each inserted, updated or deleted
+  /// row, `commit` and `rollback` each transaction. Listeners are called asynchronously once
+  /// the JS thread is free, including for changes made by async queries, and they do not
+  /// keep the process alive. Changes to `WITHOUT ROWID` tables are not reported.
+  /// @param {string} event - `update`, `commit` or `rollback`.
+  /// @param {Function} listener
+  /// @returns {this}
+  ///
+  /// Example:
+  /// ```js
+  /// db.on('update', ({ op, table, rowid }) => {
+  ///   if (table === 'users') cache.delete(rowid);
+  /// });
+  /// ```
+  #[napi(
+    strict,
+    ts_generic_types = "E extends 'update' | 'commit' | 'rollback'",
+    ts_args_type = "event: E, listener: E extends 'update' ? (event: UpdateEvent) => void : () => void"
+  )]
+  pub fn on(&mut self, env: Env, this: This, event: String, listener: JsFunction) -> Result<This> {
+    let hooks = &mut self.hooks;
+    self.handle.with(|conn| hooks.add(&env, conn, &event, &listener))?;
+    Ok(this)
+  }
+
+  /// Removes a listener added with `on()`, or every listener of the event when none is given.
+  /// @param {string} event - `update`, `commit` or `rollback`.
+  /// @param {Function} [listener]
+  /// @returns {this}
+  #[napi(
+    strict,
+    ts_args_type = "event: 'update' | 'commit' | 'rollback', listener?: (...args: any[]) => void"
+  )]
+  pub fn off(&mut self, env: Env, this: This, event: String, listener: Option<JsFunction>) -> Result<This> {
+    let hooks = &mut self.hooks;
+    self
+      .handle
+      .with(|conn| hooks.remove(&env, conn, &event, listener.as_ref()))?;
+    Ok(this)
+  }
+
+  /// Calls `listener` with the SQL of each statement that starts running, or stops when
+  /// `null` is given. Like `on()` listeners, it is called asynchronously once the JS thread
+  /// is free, and async queries are reported too.
+  /// @param {Function | null} listener
+  /// @returns {this}
+  #[napi(strict, ts_args_type = "listener: ((sql: string) => void) | null")]
+  pub fn trace(&mut self, env: Env, this: This, listener: Option<JsFunction>) -> Result<This> {
+    self.tracer.trace(&env, &self.handle, listener.as_ref())?;
+    Ok(this)
+  }
+
+  /// Calls `listener` with the SQL and the duration of each statement once it finishes,
+  /// or stops when `null` is given. It is called asynchronously like `trace()` listeners.
+  /// @param {Function | null} listener
+  /// @returns {this}
+  ///
+  /// Example:
+  /// ```js
+  /// db.profile(({ sql, duration }) => {
+  ///   if (duration > 100) logger.warn({ sql, duration }, 'slow query');
+  /// });
+  /// ```
+  #[napi(strict, ts_args_type = "listener: ((event: ProfileEvent) => void) | null")]
+  pub fn profile(&mut self, env: Env, this: This, listener: Option<JsFunction>) -> Result<This> {
+    self.tracer.profile(&env, &self.handle, listener.as_ref())?;
+    Ok(this)
+  }
+
+  /// Waits up to `ms` milliseconds for other connections to release their locks before
+  /// failing with `SQLITE_BUSY`, replacing any `busyHandler()`. `0` fails right away.
+  /// @param {number} ms
+  /// @returns {this}
+  #[napi]
+  pub fn busy_timeout(&self, this: This, ms: u32) -> Result<This> {
+    busy::timeout(&self.handle, ms)?;
+    Ok(this)
+  }
+
+  /// Decides how statements wait for other connections to release their locks, replacing
+  /// any `busyTimeout()`. The handler receives how many times the statement already retried
+  /// and returns whether to keep waiting, which it does with an exponential backoff. If the
+  /// handler throws, the statement stops waiting and throws the same exception. Without
+  /// a handler, statements retry `retries` times. Async queries cannot call the handler, and
+  /// stop after `retries` as well.
+  /// @param {Function | null} handler
+  /// @param {BusyHandlerOptions} [options]
+  /// @returns {this}
+  ///
+  /// Example:
+  /// ```js
+  /// db.busyHandler((retries) => retries < 20, { delay: 5, maxDelay: 250 });
+  /// ```
+  #[napi(
+    strict,
+    ts_args_type = "handler: ((retries: number) => boolean) | null, options?: BusyHandlerOptions"
+  )]
+  pub fn busy_handler(
+    &self,
+    env: Env,
+    this: This,
+    handler: Option<JsFunction>,
+    options: Option<BusyHandlerOptions>,
+  ) -> Result<This> {
+    BusyHandler::new(&env, handler.as_ref(), options.unwrap_or_default())?.install(&self.handle)?;
+    Ok(this)
+  }
+
+  /// Copies the database into the file at `destination` while it stays in use, which is
+  /// safe with WAL unlike copying the file. The connection cannot be closed until it finishes.
+  /// @param {string} destination - Path of the backup file, overwritten if it exists.
+  /// @param {BackupOptions} [options]
+  /// @returns {Promise<void>}
+  ///
+  /// Example:
+  /// ```js
+  /// await db.backup(`backup-${Date.now()}.db`, {
+  ///   progress: ({ totalPages, remainingPages }) => console.log(`${remainingPages}/${totalPages}`),
+  /// });
+  /// ```
+  #[napi(strict, ts_return_type = "Promise<void>")]
+  pub fn backup(
+    &self,
+    env: Env,
+    destination: String,
+    options: Option<BackupOptions>,
+  ) -> Result<AsyncTask<BackupTask>> {
+    BackupTask::new(&env, self.handle.clone(), destination, options).map(AsyncTask::new)
+  }
+
+  /// Inserts the records of a CSV file into `table` with a single prepared statement,
+  /// inside a transaction so that either every record is inserted or none is.
+  /// @param {string} table
+  /// @param {string} path
+  /// @param {CsvImportOptions} [options]
+  /// @returns {number} count - Number of records inserted.
+  ///
+  /// Example:
+  /// ```js
+  /// db.importCsv('users', './users.csv', {
+  ///   columns: { 'E-mail': 'email', Name: 'name', Active: 'active' },
+  ///   types: { active: 'boolean' },
+  /// });
+  /// ```
+  #[napi(strict)]
+  pub fn import_csv(&self, table: String, path: String, options: Option<CsvImportOptions>) -> Result<u32> {
+    self
+      .handle
+      .with(|conn| bulk::import_csv(conn, &table, &path, options.unwrap_or_default()))
+  }
+
+  /// Inserts objects into `table` with a single prepared statement, inside a transaction so
+  /// that either every row is inserted or none is. Missing fields are inserted as NULL.
+  /// @param {string} table
+  /// @param {object[]} rows
+  /// @param {ImportOptions} [options]
+  /// @returns {number} count - Number of rows inserted.
+  ///
+  /// Example:
+  /// ```js
+  /// db.importJson('events', JSON.parse(body), { columns: { id: 'id', at: 'created_at' } });
+  /// ```
+  #[napi(
+    strict,
+    ts_args_type = "table: string, rows: Record<string, unknown>[], options?: ImportOptions"
+  )]
+  pub fn import_json(&self, env: Env, table: String, rows: Array, options: Option<ImportOptions>) -> Result<u32> {
+    self
+      .handle
+      .with(|conn| bulk::import_json(&env, conn, &table, &rows, options.unwrap_or_default()))
+  }
+
+  /// Writes the rows returned by `sql` to a CSV file, replacing it if it exists.
+  /// BLOBs are written in hexadecimal.
+  /// @param {string} sql
+  /// @param {string} path
+  /// @param {CsvExportOptions} [options]
+  /// @returns {number} count - Number of rows written.
+  #[napi(strict)]
+  pub fn export_csv(&self, sql: String, path: String, options: Option<CsvExportOptions>) -> Result<u32> {
+    self
+      .handle
+      .with(|conn| bulk::export_csv(conn, &sql, &path, options.unwrap_or_default()))
+  }
+
+  /// Starts recording the changes made to `tables`, or to every table, so they can be sent
+  /// to another database as a changeset. Only tables with a PRIMARY KEY are recorded.
+  /// The connection cannot be closed until the session is.
+  /// @param {string[]} [tables]
+  /// @returns {Session} session
+  ///
+  /// Example:
+  /// ```js
+  /// const session = db.createSession(['todos', 'lists']);
+  /// // ...work offline...
+  /// await upload(session.changeset());
+  /// session.close();
+  /// ```
+  #[napi(strict)]
+  pub fn create_session(&self, tables: Option<Vec<String>>) -> Result<Session> {
+    Session::new(self.handle.clone(), tables)
+  }
+
+  /// Applies a changeset or a patchset created by a session, in a single transaction.
+  /// Conflicting changes abort it unless `onConflict` resolves them, and the values it
+  /// receives follow `safeIntegers()`.
+  /// @param {Buffer} changeset
+  /// @param {ApplyChangesetOptions} [options]
+  /// @returns {undefined}
+  ///
+  /// Example:
+  /// ```js
+  /// db.applyChangeset(await download(), {
+  ///   onConflict: ({ reason }) => (reason === 'data' ? 'replace' : 'omit'),
+  /// });
+  /// ```
+  #[napi(strict)]
+  pub fn apply_changeset(
+    &self,
+    env: Env,
+    changeset: Buffer,
+    options: Option<ApplyChangesetOptions>,
+  ) -> Result<()> {
+    self
+      .handle
+      .with(|conn| session::apply(&env, conn, &changeset, options.unwrap_or_default(), self.safe_integers))
+  }
+
+  /// Loads a SQLite extension from a shared library. Extensions run arbitrary native code,
+  /// so the database must be opened with `allowExtensions: true`.
+  /// @param {string} path - Path of the library, the platform suffix such as `.so` can be omitted.
+  /// @param {string} [entryPoint] - Name of the init function, derived from the file name by default.
+  /// @returns {this}
+  ///
+  /// Example:
+  /// ```js
+  /// const db = new Database('./geo.db', { allowExtensions: true });
+  /// db.loadExtension('./mod_spatialite');
+  /// ```
+  #[napi]
+  pub fn load_extension(&self, this: This, path: String, entry_point: Option<String>) -> Result<This> {
+    if !self.allow_extensions {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Loading extensions is disabled, open the database with `allowExtensions: true`",
+      ));
+    }
+
+    self.handle.with(|conn| {
+      // SAFETY: the user opted into extensions when opening the database, and the guard
+      // disables loading again before SQL can call `load_extension()` itself.
+      unsafe {
+        let _guard = LoadExtensionGuard::new(conn).map_err(error::sqlite)?;
+        conn
+          .load_extension(&path, entry_point.as_deref())
+          .map_err(error::sqlite)
+      }
+    })?;
+    Ok(this)
+  }
+
+  /// Opens a BLOB for incremental reads and writes, to stream large values without
+  /// loading them in memory. `createReadStream()` and `createWriteStream()` adapt it to Node streams.
+  /// @param {string} table
+  /// @param {string} column
+  /// @param {number | bigint} rowid
+  /// @param {BlobOptions} [options]
+  /// @returns {BlobHandle} blob
+  ///
+  /// Example:
+  /// ```js
+  /// const blob = db.openBlob('attachments', 'data', id, { readonly: true });
+  /// blob.createReadStream().pipe(res);
+  /// ```
+  #[napi(strict)]
+  pub fn open_blob(
+    &self,
+    table: String,
+    column: String,
+    rowid: Either<i64, BigInt>,
+    options: Option<BlobOptions>,
+  ) -> Result<BlobHandle> {
+    let rowid = match rowid {
+      Either::A(rowid) => rowid,
+      Either::B(rowid) => match rowid.get_i64() {
+        (rowid, true) => rowid,
+        _ => {
+          return Err(Error::new(
+            Status::InvalidArg,
+            "BigInt is out of range for a 64-bit integer",
+          ))
+        }
+      },
+    };
+    BlobHandle::new(self.handle.clone(), table, column, rowid, options.unwrap_or_default())
+  }
+
+  /// Re-encrypts a database opened with a `key` using a new one, by rewriting every page
+  /// in a single transaction. Other connections must reopen the database with the new key.
+  /// If the process stops halfway, opening the database with the old key rolls the change back.
+  /// @param {Buffer} key - The new 32-byte key.
+  /// @returns {undefined}
+  ///
+  /// Example:
+  /// ```js
+  /// const db = new Database('./cache.db', { key: oldKey });
+  /// db.rekey(crypto.randomBytes(32));
+  /// ```
+  #[napi(strict)]
+  pub fn rekey(&self, key: Buffer) -> Result<()> {
+    self.handle.with(|conn| cipher::rekey(conn, &key))
+  }
+
+  /// Returns a copy of the content of the database as a Buffer, which can be
+  /// written to a file or opened again with `Database.deserialize()`.
+  /// @param {string} [schema='main'] - Name of the database, such as `temp` or an attached one.
+  /// @returns {Buffer} buffer
+  #[napi]
+  pub fn serialize(&self, schema: Option<String>) -> Result<Buffer> {
+    self.handle.with(|conn| serialize::serialize(conn, schema.as_deref()))
+  }
+
+  /// Returns INTEGER values as `BigInt` in every statement prepared from now on.
+  /// Each statement can still change it with `Statement.safeIntegers()`.
+  /// @param {boolean} [toggle=true]
+  /// @returns {this}
+  #[napi]
+  pub fn safe_integers(&mut self, this: This, toggle: Option<bool>) -> This {
+    self.safe_integers = toggle.unwrap_or(true);
+    this
+  }
+
+  /// Closes the connection. Calling it on a closed connection does nothing.
+  /// @returns {undefined}
+  #[napi]
+  pub fn close(&self) -> Result<()> {
+    self.handle.close()
+  }
+}
+
+impl Database {
+  fn from_connection(name: String, conn: Connection) -> Self {
+    Database {
+      filename: conn.path().unwrap_or_default().to_string(),
+      name,
+      safe_integers: false,
+      allow_extensions: false,
+      hooks: Hooks::default(),
+      tracer: Tracer::default(),
+      handle: Handle::new(conn),
+    }
+  }
+}
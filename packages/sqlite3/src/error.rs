@@ -0,0 +1,225 @@
+use super::reference::Slot;
+use napi::{bindgen_prelude::ToNapiValue, sys, Env, Error, JsUnknown, NapiValue, Result, Status};
+use rusqlite::ffi;
+use std::cell::{Cell, RefCell};
+
+thread_local! {
+  /// The JS environment of the current thread, set when the module loads on it.
+  static ENV: Cell<Option<sys::napi_env>> = const { Cell::new(None) };
+
+  /// The last SQLite error raised on a thread pool thread, until its task rejects.
+  static LAST: RefCell<Option<(String, Details)>> = const { RefCell::new(None) };
+
+  /// The exception a JS callback threw on this thread, until the statement it failed returns.
+  static THROWN: RefCell<Option<Slot>> = const { RefCell::new(None) };
+}
+
+/// What SQLite reported about a failure, set as properties of the JS error.
+#[derive(Clone, Copy, Debug)]
+pub struct Details {
+  extended_code: i32,
+  offset: Option<i32>,
+}
+
+impl Details {
+  fn new(err: &rusqlite::Error) -> Option<Self> {
+    match err {
+      rusqlite::Error::SqliteFailure(error, _) => Some(Details {
+        extended_code: error.extended_code,
+        offset: None,
+      }),
+      rusqlite::Error::SqlInputError { error, offset, .. } => Some(Details {
+        extended_code: error.extended_code,
+        offset: Some(*offset),
+      }),
+      _ => None,
+    }
+  }
+
+  /// Creates the JS error for `reason`, falling back to a plain one if that fails.
+  fn into_error(self, env: &Env, reason: String) -> Error {
+    match self.to_js(env, &reason) {
+      Ok(value) => {
+        let mut error = Error::from(value);
+        error.reason = reason;
+        error
+      }
+      Err(_) => Error::new(Status::GenericFailure, reason),
+    }
+  }
+
+  /// Creates the JS error for `reason`, with `code`, `errno`, `extendedCode` and `offset`.
+  fn to_js(self, env: &Env, reason: &str) -> Result<JsUnknown> {
+    let mut error = env.create_error(Error::new(Status::GenericFailure, reason))?;
+    error.set_named_property("code", env.create_string(code(self.extended_code))?)?;
+    error.set_named_property("errno", env.create_int32(self.extended_code & 0xff)?)?;
+    error.set_named_property("extendedCode", env.create_int32(self.extended_code)?)?;
+    if let Some(offset) = self.offset {
+      error.set_named_property("offset", env.create_int32(offset)?)?;
+    }
+    Ok(error.into_unknown())
+  }
+}
+
+/// Remembers the JS environment of the thread loading the module, so errors raised
+/// on it can be created as JS errors right away.
+pub fn init(env: &Env) {
+  ENV.with(|cell| cell.set(Some(env.raw())));
+}
+
+/// Converts a rusqlite error into a napi error.
+///
+/// Errors reported by SQLite become JS errors with the name of the result code as `code`,
+/// such as `SQLITE_CONSTRAINT_UNIQUE`. On the thread pool, where no JS value can be
+/// created, the details are kept until [`take`] hands them to the rejecting task.
+pub fn sqlite(err: rusqlite::Error) -> Error {
+  let reason = err.to_string();
+  from_sqlite(&err, reason)
+}
+
+/// Converts a rusqlite error like [`sqlite`], prefixing its message with what was running.
+pub fn sqlite_in(context: &str, err: rusqlite::Error) -> Error {
+  let reason = format!("{context}: {err}");
+  from_sqlite(&err, reason)
+}
+
+fn from_sqlite(err: &rusqlite::Error, reason: String) -> Error {
+  let Some(details) = Details::new(err) else {
+    return Error::new(Status::GenericFailure, reason);
+  };
+
+  match ENV.with(Cell::get) {
+    // SAFETY: the environment belongs to this thread, which only runs native code
+    // when JS calls into the module.
+    Some(env) => details.into_error(&unsafe { Env::from_raw(env) }, reason),
+    None => {
+      LAST.with(|last| last.replace(Some((reason.clone(), details))));
+      Error::new(Status::GenericFailure, reason)
+    }
+  }
+}
+
+/// Takes the details of `err` when it is the last SQLite error raised on this thread.
+pub fn take(err: &Error) -> Option<Details> {
+  LAST
+    .with(RefCell::take)
+    .and_then(|(reason, details)| (reason == err.reason).then_some(details))
+}
+
+/// Attaches the details taken on the thread pool to an error rejecting a task.
+pub fn reject(env: &Env, err: Error, details: Option<Details>) -> Error {
+  match details {
+    Some(details) => details.into_error(env, err.reason),
+    None => err,
+  }
+}
+
+/// Keeps the exception carried by `err`, thrown by a JS callback that SQLite called,
+/// so the failing statement rethrows it instead of the message SQLite reports.
+/// Returns that message.
+///
+/// The reference `err` holds to the exception is always released, and only the first
+/// exception is kept until [`rethrow`] takes it.
+pub fn keep(err: Error) -> String {
+  let reason = err.reason.clone();
+  let Some(env) = ENV.with(Cell::get) else {
+    return reason;
+  };
+
+  // SAFETY: the environment belongs to this thread. Converting the error into its JS value
+  // deletes the reference it holds.
+  let env = unsafe { Env::from_raw(env) };
+  if let Ok(value) = unsafe { Error::to_napi_value(env.raw(), err) } {
+    let value = unsafe { JsUnknown::from_raw_unchecked(env.raw(), value) };
+    if let Ok(slot) = Slot::new(&env, value) {
+      THROWN.with(|thrown| {
+        thrown.borrow_mut().get_or_insert(slot);
+      });
+    }
+  }
+  reason
+}
+
+/// Settles the result of running a statement, replacing its error with the exception
+/// a JS callback threw while it ran, if any. A kept exception is released either way.
+pub fn rethrow<T>(result: Result<T>) -> Result<T> {
+  let thrown = THROWN.with(RefCell::take);
+  match (result, thrown, ENV.with(Cell::get)) {
+    (Err(err), Some(thrown), Some(env)) => match thrown.get() {
+      Ok(exception) => {
+        // Releases the JS error created for what SQLite reported.
+        drop(unsafe { Error::to_napi_value(env, err) });
+        Err(Error::from(exception))
+      }
+      Err(_) => Err(err),
+    },
+    (result, ..) => result,
+  }
+}
+
+/// Error returned when the connection was already closed.
+pub fn not_open() -> Error {
+  Error::new(Status::GenericFailure, "The database connection is not open")
+}
+
+/// Error returned when the connection is in use by an iterator or an async task.
+pub fn busy() -> Error {
+  Error::new(
+    Status::GenericFailure,
+    "This database connection is busy executing a query",
+  )
+}
+
+/// Error returned when SQLite calls a JS function from a query running on the thread pool.
+pub fn wrong_thread() -> Error {
+  Error::new(
+    Status::GenericFailure,
+    "JS functions cannot be called from async queries, use the synchronous API instead",
+  )
+}
+
+macro_rules! codes {
+  ($($name:ident),* $(,)?) => {
+    /// Returns the name of a SQLite result code, extended or primary.
+    fn code(code: i32) -> &'static str {
+      match code {
+        $(ffi::$name => stringify!($name),)*
+        // Codes added by newer SQLite versions fall back to their primary code.
+        _ if code > 0xff => self::code(code & 0xff),
+        _ => "SQLITE_ERROR",
+      }
+    }
+  };
+}
+
+codes! {
+  SQLITE_ERROR, SQLITE_INTERNAL, SQLITE_PERM, SQLITE_ABORT, SQLITE_BUSY, SQLITE_LOCKED,
+  SQLITE_NOMEM, SQLITE_READONLY, SQLITE_INTERRUPT, SQLITE_IOERR, SQLITE_CORRUPT, SQLITE_NOTFOUND,
+  SQLITE_FULL, SQLITE_CANTOPEN, SQLITE_PROTOCOL, SQLITE_EMPTY, SQLITE_SCHEMA, SQLITE_TOOBIG,
+  SQLITE_CONSTRAINT, SQLITE_MISMATCH, SQLITE_MISUSE, SQLITE_NOLFS, SQLITE_AUTH, SQLITE_FORMAT,
+  SQLITE_RANGE, SQLITE_NOTADB, SQLITE_NOTICE, SQLITE_WARNING,
+  SQLITE_ERROR_MISSING_COLLSEQ, SQLITE_ERROR_RETRY, SQLITE_ERROR_SNAPSHOT,
+  SQLITE_IOERR_READ, SQLITE_IOERR_SHORT_READ, SQLITE_IOERR_WRITE, SQLITE_IOERR_FSYNC,
+  SQLITE_IOERR_DIR_FSYNC, SQLITE_IOERR_TRUNCATE, SQLITE_IOERR_FSTAT, SQLITE_IOERR_UNLOCK,
+  SQLITE_IOERR_RDLOCK, SQLITE_IOERR_DELETE, SQLITE_IOERR_BLOCKED, SQLITE_IOERR_NOMEM,
+  SQLITE_IOERR_ACCESS, SQLITE_IOERR_CHECKRESERVEDLOCK, SQLITE_IOERR_LOCK, SQLITE_IOERR_CLOSE,
+  SQLITE_IOERR_DIR_CLOSE, SQLITE_IOERR_SHMOPEN, SQLITE_IOERR_SHMSIZE, SQLITE_IOERR_SHMLOCK,
+  SQLITE_IOERR_SHMMAP, SQLITE_IOERR_SEEK, SQLITE_IOERR_DELETE_NOENT, SQLITE_IOERR_MMAP,
+  SQLITE_IOERR_GETTEMPPATH, SQLITE_IOERR_CONVPATH, SQLITE_IOERR_VNODE, SQLITE_IOERR_AUTH,
+  SQLITE_IOERR_BEGIN_ATOMIC, SQLITE_IOERR_COMMIT_ATOMIC, SQLITE_IOERR_ROLLBACK_ATOMIC,
+  SQLITE_IOERR_DATA, SQLITE_IOERR_CORRUPTFS, SQLITE_IOERR_IN_PAGE,
+  SQLITE_LOCKED_SHAREDCACHE, SQLITE_LOCKED_VTAB,
+  SQLITE_BUSY_RECOVERY, SQLITE_BUSY_SNAPSHOT, SQLITE_BUSY_TIMEOUT,
+  SQLITE_CANTOPEN_NOTEMPDIR, SQLITE_CANTOPEN_ISDIR, SQLITE_CANTOPEN_FULLPATH,
+  SQLITE_CANTOPEN_CONVPATH, SQLITE_CANTOPEN_DIRTYWAL, SQLITE_CANTOPEN_SYMLINK,
+  SQLITE_CORRUPT_VTAB, SQLITE_CORRUPT_SEQUENCE, SQLITE_CORRUPT_INDEX,
+  SQLITE_READONLY_RECOVERY, SQLITE_READONLY_CANTLOCK, SQLITE_READONLY_ROLLBACK,
+  SQLITE_READONLY_DBMOVED, SQLITE_READONLY_CANTINIT, SQLITE_READONLY_DIRECTORY,
+  SQLITE_ABORT_ROLLBACK,
+  SQLITE_CONSTRAINT_CHECK, SQLITE_CONSTRAINT_COMMITHOOK, SQLITE_CONSTRAINT_FOREIGNKEY,
+  SQLITE_CONSTRAINT_FUNCTION, SQLITE_CONSTRAINT_NOTNULL, SQLITE_CONSTRAINT_PRIMARYKEY,
+  SQLITE_CONSTRAINT_TRIGGER, SQLITE_CONSTRAINT_UNIQUE, SQLITE_CONSTRAINT_VTAB,
+  SQLITE_CONSTRAINT_ROWID, SQLITE_CONSTRAINT_PINNED, SQLITE_CONSTRAINT_DATATYPE,
+  SQLITE_NOTICE_RECOVER_WAL, SQLITE_NOTICE_RECOVER_ROLLBACK, SQLITE_NOTICE_RBU,
+  SQLITE_WARNING_AUTOINDEX, SQLITE_AUTH_USER,
+}
@@ -0,0 +1,159 @@
+use super::{
+  error,
+  reference::JsRef,
+  value::{self, SqlValue},
+};
+use napi::{
+  bindgen_prelude::ToNapiValue, Env, JsFunction, JsNumber, JsObject, JsUnknown, NapiRaw, NapiValue, Result,
+};
+use napi_derive::napi;
+use rusqlite::{
+  functions::{Context, FunctionFlags},
+  types::Value,
+  Connection,
+};
+
+/// Options for a user-defined function.
+#[napi(object)]
+#[derive(Default)]
+pub struct FunctionOptions {
+  /// Whether the function always returns the same result for the same arguments,
+  /// which lets SQLite use it in indexes and optimize repeated calls.
+  /// @type {boolean} [deterministic=false]
+  pub deterministic: Option<bool>,
+
+  /// Whether the function accepts any number of arguments instead of `fn.length`.
+  /// @type {boolean} [varargs=false]
+  pub varargs: Option<bool>,
+
+  /// Whether the function can only be called from top-level SQL,
+  /// and not from views, triggers or schema structures.
+  /// @type {boolean} [directOnly=false]
+  pub direct_only: Option<bool>,
+}
+
+impl FunctionOptions {
+  /// Flags to register the function with.
+  pub fn flags(&self) -> FunctionFlags {
+    let mut flags = FunctionFlags::SQLITE_UTF8;
+    if self.deterministic.unwrap_or(false) {
+      flags |= FunctionFlags::SQLITE_DETERMINISTIC;
+    }
+    if self.direct_only.unwrap_or(false) {
+      flags |= FunctionFlags::SQLITE_DIRECTONLY;
+    }
+    flags
+  }
+
+  /// Number of arguments the function accepts, `-1` for any number.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the `length` of the callback cannot be read.
+  pub fn arity(&self, env: &Env, callback: &JsFunction) -> Result<i32> {
+    if self.varargs.unwrap_or(false) {
+      return Ok(-1);
+    }
+    let object = unsafe { JsObject::from_raw_unchecked(env.raw(), callback.raw()) };
+    object.get_named_property::<JsNumber>("length")?.get_int32()
+  }
+}
+
+/// A JS callback invoked by SQLite.
+pub struct Callback {
+  env: Env,
+  function: JsRef,
+  safe_integers: bool,
+}
+
+// SAFETY: SQLite may invoke the callback from a query running on the thread pool,
+// in which case it returns an error without touching any JS value.
+unsafe impl Send for Callback {}
+
+impl Callback {
+  pub fn new(env: &Env, function: &JsFunction, safe_integers: bool) -> Result<Self> {
+    Ok(Callback {
+      env: *env,
+      function: JsRef::new(env, function)?,
+      safe_integers,
+    })
+  }
+
+  /// Calls the callback with `args`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the callback throws.
+  pub fn call(&self, args: &[JsUnknown]) -> Result<JsUnknown> {
+    self.function.get::<JsFunction>()?.call(None, args)
+  }
+
+  /// The environment of the JS thread the callback belongs to.
+  pub fn env(&self) -> &Env {
+    &self.env
+  }
+
+  /// Whether the callback can be called from the current thread, which is not
+  /// the case for queries running on the thread pool.
+  pub fn is_callable(&self) -> bool {
+    self.function.check_thread().is_ok()
+  }
+
+  /// Converts a value into a JS argument for the callback.
+  pub fn argument(&self, value: Value) -> Result<JsUnknown> {
+    self.function.check_thread()?;
+    self.to_js(value)
+  }
+
+  /// Converts any value, such as an event object, into a JS argument for the callback.
+  pub fn convert<T: ToNapiValue>(&self, value: T) -> Result<JsUnknown> {
+    self.function.check_thread()?;
+    let raw = unsafe { T::to_napi_value(self.env.raw(), value)? };
+    Ok(unsafe { JsUnknown::from_raw_unchecked(self.env.raw(), raw) })
+  }
+
+  /// Converts the arguments SQLite passed to the function into JS values.
+  pub fn arguments(&self, ctx: &Context) -> Result<Vec<JsUnknown>> {
+    self.function.check_thread()?;
+    (0..ctx.len()).map(|i| self.to_js(ctx.get_raw(i).into())).collect()
+  }
+
+  /// Converts a SQLite value into a JS value.
+  fn to_js(&self, value: Value) -> Result<JsUnknown> {
+    let raw = unsafe { SqlValue::to_napi_value(self.env.raw(), SqlValue::new(value, self.safe_integers))? };
+    Ok(unsafe { JsUnknown::from_raw_unchecked(self.env.raw(), raw) })
+  }
+}
+
+/// Converts an Error raised by JS into one SQLite reports for the failing call,
+/// see [`error::keep`].
+pub fn user_error(err: napi::Error) -> rusqlite::Error {
+  rusqlite::Error::UserFunctionError(error::keep(err).into())
+}
+
+/// Registers `callback` as a scalar function called `name`.
+///
+/// # Errors
+///
+/// Returns an Error if the callback cannot be referenced or SQLite rejects the function.
+pub fn create_scalar(
+  env: &Env,
+  conn: &Connection,
+  name: &str,
+  options: &FunctionOptions,
+  callback: &JsFunction,
+  safe_integers: bool,
+) -> Result<()> {
+  let arity = options.arity(env, callback)?;
+  let callback = Callback::new(env, callback, safe_integers)?;
+
+  conn
+    .create_scalar_function(name, arity, options.flags(), move |ctx| {
+      callback
+        .arguments(ctx)
+        .and_then(|args| callback.call(&args))
+        .and_then(value::to_value)
+        .map_err(user_error)
+    })
+    .map_err(error::sqlite)
+}
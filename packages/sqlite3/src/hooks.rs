@@ -0,0 +1,187 @@
+use super::reference::JsRef;
+use napi::{
+  bindgen_prelude::ToNapiValue,
+  threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+  Env, Error, JsFunction, JsUnknown, Result, Status,
+};
+use napi_derive::napi;
+use rusqlite::{hooks::Action, Connection};
+
+/// A listener called from whichever thread runs the statement, async queries included.
+type Callback = ThreadsafeFunction<Option<UpdateEvent>, ErrorStrategy::Fatal>;
+
+/// Wraps a listener so that it can be called from any thread, with the arguments `args`
+/// makes of each value. Listeners must not keep the process alive on their own, so the
+/// wrapper is unreferenced.
+///
+/// # Errors
+///
+/// Returns an Error if the listener cannot be wrapped.
+pub fn threadsafe<T: 'static, V: ToNapiValue + 'static>(
+  env: &Env,
+  listener: &JsFunction,
+  args: fn(T) -> Vec<V>,
+) -> Result<ThreadsafeFunction<T, ErrorStrategy::Fatal>> {
+  let mut callback =
+    listener.create_threadsafe_function(0, move |ctx: ThreadSafeCallContext<T>| Ok(args(ctx.value)))?;
+  callback.unref(env)?;
+  Ok(callback)
+}
+
+/// A row change, given to `update` listeners.
+#[napi(object)]
+#[derive(Clone)]
+pub struct UpdateEvent {
+  /// @type {string} op - `insert`, `update` or `delete`.
+  #[napi(ts_type = "'insert' | 'update' | 'delete'")]
+  pub op: String,
+
+  /// @type {string} database - Name of the database, such as `main` or an attached one.
+  pub database: String,
+
+  /// @type {string} table
+  pub table: String,
+
+  /// @type {number} rowid - Rowid of the changed row.
+  pub rowid: i64,
+}
+
+/// Events that can be listened to with `Database.on()`.
+#[derive(Clone, Copy)]
+enum Event {
+  Update,
+  Commit,
+  Rollback,
+}
+
+impl Event {
+  fn parse(name: &str) -> Result<Self> {
+    match name {
+      "update" => Ok(Event::Update),
+      "commit" => Ok(Event::Commit),
+      "rollback" => Ok(Event::Rollback),
+      _ => Err(Error::new(
+        Status::InvalidArg,
+        format!("Unknown event \"{name}\", expected update, commit or rollback"),
+      )),
+    }
+  }
+}
+
+struct Listener {
+  function: JsRef,
+  callback: Callback,
+}
+
+/// Listeners of a connection, installed as SQLite hooks while an event has any.
+#[derive(Default)]
+pub struct Hooks {
+  update: Vec<Listener>,
+  commit: Vec<Listener>,
+  rollback: Vec<Listener>,
+}
+
+impl Hooks {
+  /// Adds a listener to an event.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the event is unknown or the listener cannot be referenced.
+  pub fn add(&mut self, env: &Env, conn: &Connection, event: &str, listener: &JsFunction) -> Result<()> {
+    let event = Event::parse(event)?;
+    let callback: Callback = threadsafe(env, listener, |event: Option<UpdateEvent>| {
+      event.into_iter().collect::<Vec<_>>()
+    })?;
+
+    self.listeners(event).push(Listener {
+      function: JsRef::new(env, listener)?,
+      callback,
+    });
+    self.install(conn, event);
+    Ok(())
+  }
+
+  /// Removes a listener from an event, or every listener when none is given.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the event is unknown.
+  pub fn remove(
+    &mut self,
+    env: &Env,
+    conn: &Connection,
+    event: &str,
+    listener: Option<&JsFunction>,
+  ) -> Result<()> {
+    let event = Event::parse(event)?;
+    match listener {
+      Some(listener) => {
+        let mut index = None;
+        for (i, current) in self.listeners(event).iter().enumerate() {
+          if env.strict_equals(current.function.get::<JsUnknown>()?, listener)? {
+            index = Some(i);
+          }
+        }
+        // Like `EventEmitter`, the most recently added copy of the listener goes first.
+        if let Some(index) = index {
+          self.listeners(event).remove(index);
+        }
+      }
+      None => self.listeners(event).clear(),
+    }
+
+    self.install(conn, event);
+    Ok(())
+  }
+
+  fn listeners(&mut self, event: Event) -> &mut Vec<Listener> {
+    match event {
+      Event::Update => &mut self.update,
+      Event::Commit => &mut self.commit,
+      Event::Rollback => &mut self.rollback,
+    }
+  }
+
+  /// Replaces the SQLite hook of an event with one calling its current listeners.
+  fn install(&mut self, conn: &Connection, event: Event) {
+    let callbacks = self
+      .listeners(event)
+      .iter()
+      .map(|listener| listener.callback.clone())
+      .collect::<Vec<_>>();
+    let active = !callbacks.is_empty();
+
+    match event {
+      Event::Update => conn.update_hook(active.then_some(
+        move |action: Action, database: &str, table: &str, rowid: i64| {
+          let op = match action {
+            Action::SQLITE_INSERT => "insert",
+            Action::SQLITE_UPDATE => "update",
+            Action::SQLITE_DELETE => "delete",
+            _ => return,
+          };
+          let event = UpdateEvent {
+            op: op.to_string(),
+            database: database.to_string(),
+            table: table.to_string(),
+            rowid,
+          };
+          emit(&callbacks, Some(event));
+        },
+      )),
+      Event::Commit => conn.commit_hook(active.then_some(move || {
+        emit(&callbacks, None);
+        // Returning true would turn the commit into a rollback.
+        false
+      })),
+      Event::Rollback => conn.rollback_hook(active.then_some(move || emit(&callbacks, None))),
+    }
+  }
+}
+
+/// Queues a call to every listener, which runs once the JS thread is free.
+fn emit(callbacks: &[Callback], event: Option<UpdateEvent>) {
+  for callback in callbacks {
+    callback.call(event.clone(), ThreadsafeFunctionCallMode::NonBlocking);
+  }
+}
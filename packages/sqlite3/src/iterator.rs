@@ -0,0 +1,106 @@
+use super::{
+  cache,
+  connection::Handle,
+  error,
+  row::{Format, Layout, Row, Step},
+  value::Params,
+};
+use napi::{bindgen_prelude::Generator, Result};
+use napi_derive::napi;
+use rusqlite::{CachedStatement, Connection, Rows};
+use std::sync::Arc;
+
+/// Iterator over the rows of a statement, created with `Statement.iterate(params)`.
+#[napi(iterator)]
+pub struct StatementIterator {
+  // Borrows `stmt`, so it must be dropped first.
+  rows: Option<Rows<'static>>,
+  stmt: *mut CachedStatement<'static>,
+  layout: Arc<Layout>,
+  handle: Handle,
+}
+
+impl StatementIterator {
+  /// Executes the statement, keeping it alive until every row is read.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the connection is closed, async queries are queued on it
+  /// or the statement fails to start.
+  pub fn new(handle: Handle, source: &str, format: &Format, params: &Params) -> Result<Self> {
+    handle.check_idle()?;
+    let guard = handle.lock();
+    let slot = guard.borrow();
+    let conn = slot.as_ref().ok_or_else(error::not_open)?;
+
+    // SAFETY: see `Handle::acquire`.
+    let conn: &'static Connection = unsafe { &*(conn as *const Connection) };
+    let mut stmt = cache::prepare(conn, source)?;
+    let layout = Layout::new(conn, &stmt, source, format)?;
+    params.bind(&mut stmt)?;
+
+    // SAFETY: the statement is heap allocated and only freed in `finish`, after the rows.
+    let stmt = Box::into_raw(Box::new(stmt));
+    let rows = unsafe { &mut *stmt }.raw_query();
+
+    handle.acquire();
+    Ok(StatementIterator {
+      layout,
+      rows: Some(rows),
+      stmt,
+      handle: handle.clone(),
+    })
+  }
+
+  /// Resets the statement, returns it to the cache and releases the connection.
+  fn finish(&mut self) {
+    if let Some(rows) = self.rows.take() {
+      let _guard = self.handle.lock();
+      drop(rows);
+      drop(unsafe { Box::from_raw(self.stmt) });
+      self.handle.release();
+    }
+  }
+}
+
+impl Drop for StatementIterator {
+  fn drop(&mut self) {
+    self.finish();
+  }
+}
+
+#[napi]
+impl Generator for StatementIterator {
+  type Yield = Step;
+  type Next = ();
+  type Return = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    // The iterator stays usable once the async queries finish.
+    if let Err(err) = self.handle.check_idle() {
+      self.rows.as_ref()?;
+      return Some(Step(Err(err)));
+    }
+
+    let guard = self.handle.lock();
+    let step = match error::rethrow(self.rows.as_mut()?.next().map_err(error::sqlite)) {
+      Ok(Some(row)) => Ok(Row::read(&self.layout, row)),
+      Ok(None) => Err(None),
+      Err(err) => Err(Some(err)),
+    };
+    drop(guard);
+
+    match step {
+      Ok(row) => Some(Step(row)),
+      Err(err) => {
+        self.finish();
+        err.map(|err| Step(Err(err)))
+      }
+    }
+  }
+
+  fn complete(&mut self, _value: Option<Self::Return>) -> Option<Self::Yield> {
+    self.finish();
+    None
+  }
+}
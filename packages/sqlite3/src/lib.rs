@@ -2,10 +2,43 @@
 
 extern crate napi_allocator;
 
-#[macro_use]
-extern crate napi_derive;
+mod aggregate;
+mod backup;
+mod blob;
+mod bulk;
+mod busy;
+mod cache;
+mod cipher;
+mod column;
+mod connection;
+mod csv;
+mod database;
+mod error;
+mod function;
+mod hooks;
+mod iterator;
+mod migrate;
+mod options;
+mod plan;
+mod pool;
+mod pragma;
+mod reference;
+mod row;
+mod serialize;
+mod session;
+mod sql;
+mod statement;
+mod table;
+mod task;
+mod trace;
+mod transaction;
+mod value;
 
-#[napi]
-pub fn floor(n: f64) -> f64 {
-  n.floor()
+use napi::{Env, JsObject, Result};
+use napi_derive::module_exports;
+
+#[module_exports]
+fn init(_exports: JsObject, env: Env) -> Result<()> {
+  error::init(&env);
+  Ok(())
 }
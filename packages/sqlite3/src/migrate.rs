@@ -0,0 +1,272 @@
+use super::{error, sql::quote, transaction::savepoint};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use rusqlite::{params, Connection};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Table recording the applied migrations when `table` is not given.
+const DEFAULT_TABLE: &str = "migrations";
+
+/// Savepoint wrapping every migration, so they also work inside a transaction.
+const SAVEPOINT: &str = "\"_migration\"";
+
+/// Line separating the `up` and `down` scripts of a migration file.
+const DOWN_MARKER: &str = "-- down";
+
+/// A schema migration.
+#[napi(object)]
+#[derive(Clone)]
+pub struct Migration {
+  /// @type {number} version - Migrations are applied in ascending order of version.
+  pub version: u32,
+
+  /// @type {string} name
+  pub name: String,
+
+  /// @type {string} up - SQL applying the migration.
+  pub up: String,
+
+  /// @type {string} [down] - SQL reverting the migration.
+  pub down: Option<String>,
+}
+
+/// Options for `Database.migrate()`.
+#[napi(object, object_to_js = false)]
+pub struct MigrateOptions {
+  /// Directory holding `<version>-<name>.sql` files, where an optional `-- down` line
+  /// separates the SQL applying the migration from the SQL reverting it.
+  /// @type {string} [directory]
+  pub directory: Option<String>,
+
+  /// Migrations to apply, instead of reading them from a directory.
+  /// @type {Migration[]} [migrations]
+  pub migrations: Option<Vec<Migration>>,
+
+  /// Name of the table recording the applied migrations.
+  /// @type {string} [table='migrations']
+  pub table: Option<String>,
+
+  /// Version to migrate to, lower versions are reverted with their `down` script. Defaults to the latest one.
+  /// @type {number} [to]
+  pub to: Option<u32>,
+}
+
+/// Outcome of `Database.migrate()`.
+#[napi(object)]
+pub struct MigrateResult {
+  /// @type {number} version - The version of the schema after migrating, `0` without migrations.
+  pub version: u32,
+
+  /// @type {number[]} applied - Versions applied, in order.
+  pub applied: Vec<u32>,
+
+  /// @type {number[]} reverted - Versions reverted, in order.
+  pub reverted: Vec<u32>,
+}
+
+/// Applies pending migrations, or reverts applied ones down to `options.to`.
+///
+/// Each migration runs in its own savepoint and is recorded in the tracking table
+/// with a checksum of its `up` script.
+///
+/// # Errors
+///
+/// Returns an Error if the migrations cannot be read, an applied migration is missing
+/// or changed, a migration to revert has no `down` script, or a migration fails.
+pub fn migrate(conn: &Connection, options: MigrateOptions) -> Result<MigrateResult> {
+  let migrations = match (options.directory, options.migrations) {
+    (Some(directory), None) => read_directory(Path::new(&directory))?,
+    (None, Some(migrations)) => migrations,
+    _ => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Expected either a directory or a list of migrations",
+      ))
+    }
+  };
+
+  let mut by_version = HashMap::with_capacity(migrations.len());
+  for migration in &migrations {
+    if migration.version == 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Migration \"{}\" must have a version greater than 0", migration.name),
+      ));
+    }
+    if by_version.insert(migration.version, migration).is_some() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Duplicate migration version {}", migration.version),
+      ));
+    }
+  }
+
+  let table = quote(options.table.as_deref().unwrap_or(DEFAULT_TABLE));
+  conn
+    .execute_batch(&format!(
+      "CREATE TABLE IF NOT EXISTS {table} (
+        version INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        checksum TEXT NOT NULL,
+        applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+      )"
+    ))
+    .map_err(error::sqlite)?;
+
+  let applied = applied_versions(conn, &table)?;
+  for (version, checksum) in &applied {
+    let migration = by_version.get(version).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Applied migration {version} is missing"),
+      )
+    })?;
+    if &checksum_of(&migration.up) != checksum {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!(
+          "Migration {version} ({}) was changed after it was applied",
+          migration.name
+        ),
+      ));
+    }
+  }
+
+  let current = applied.last().map_or(0, |(version, _)| *version);
+  let target = options
+    .to
+    .unwrap_or_else(|| migrations.iter().map(|m| m.version).max().unwrap_or(0));
+  let mut result = MigrateResult {
+    version: current,
+    applied: Vec::new(),
+    reverted: Vec::new(),
+  };
+
+  if target >= current {
+    let mut pending = migrations
+      .iter()
+      .filter(|m| m.version <= target && !applied.iter().any(|(version, _)| *version == m.version))
+      .collect::<Vec<_>>();
+    pending.sort_by_key(|m| m.version);
+
+    for migration in pending {
+      let record = format!("INSERT INTO {table} (version, name, checksum) VALUES (?1, ?2, ?3)");
+      run(conn, migration, &migration.up, |conn| {
+        conn.execute(
+          &record,
+          params![migration.version, migration.name, checksum_of(&migration.up)],
+        )
+      })?;
+      result.applied.push(migration.version);
+      result.version = migration.version;
+    }
+  } else {
+    for (version, _) in applied.iter().rev().filter(|(version, _)| *version > target) {
+      let migration = by_version[version];
+      let down = migration.down.as_deref().ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Migration {version} ({}) has no down script", migration.name),
+        )
+      })?;
+      let record = format!("DELETE FROM {table} WHERE version = ?1");
+      run(conn, migration, down, |conn| conn.execute(&record, [version]))?;
+      result.reverted.push(*version);
+    }
+    result.version = applied
+      .iter()
+      .map(|(version, _)| *version)
+      .filter(|version| *version <= target)
+      .max()
+      .unwrap_or(0);
+  }
+
+  Ok(result)
+}
+
+/// Runs `sql` and then `record` inside a savepoint, rolling both back if either fails.
+fn run<F>(conn: &Connection, migration: &Migration, sql: &str, record: F) -> Result<()>
+where
+  F: FnOnce(&Connection) -> rusqlite::Result<usize>,
+{
+  savepoint(conn, SAVEPOINT, || {
+    conn.execute_batch(sql).and_then(|_| record(conn)).map_err(|err| {
+      error::sqlite_in(
+        &format!("Migration {} ({}) failed", migration.version, migration.name),
+        err,
+      )
+    })?;
+    Ok(())
+  })
+}
+
+/// Returns the applied versions with their checksum, in ascending order.
+fn applied_versions(conn: &Connection, table: &str) -> Result<Vec<(u32, String)>> {
+  let mut stmt = conn
+    .prepare(&format!("SELECT version, checksum FROM {table} ORDER BY version"))
+    .map_err(error::sqlite)?;
+  let rows = stmt
+    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+    .map_err(error::sqlite)?;
+  rows.collect::<rusqlite::Result<_>>().map_err(error::sqlite)
+}
+
+/// Reads the `<version>-<name>.sql` files of a directory.
+fn read_directory(directory: &Path) -> Result<Vec<Migration>> {
+  let read_error = |err: std::io::Error| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Cannot read migrations from \"{}\": {err}", directory.display()),
+    )
+  };
+
+  let mut migrations = Vec::new();
+  for entry in fs::read_dir(directory).map_err(read_error)? {
+    let path = entry.map_err(read_error)?.path();
+    if !path.is_file() || path.extension() != Some("sql".as_ref()) {
+      continue;
+    }
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let digits = stem.chars().take_while(char::is_ascii_digit).count();
+    let version = stem[..digits].parse::<u32>().map_err(|_| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Migration file \"{stem}.sql\" must start with its version number"),
+      )
+    })?;
+    let name = stem[digits..].trim_start_matches(['-', '_', '.', ' ']).to_string();
+
+    let content = fs::read_to_string(&path).map_err(read_error)?.replace("\r\n", "\n");
+    let (up, down) = split_down(&content);
+    migrations.push(Migration {
+      version,
+      name,
+      up: up.to_string(),
+      down: down.map(str::to_string),
+    });
+  }
+
+  Ok(migrations)
+}
+
+/// Splits a migration file at its `-- down` line.
+fn split_down(content: &str) -> (&str, Option<&str>) {
+  let mut offset = 0;
+  for line in content.split_inclusive('\n') {
+    if line.trim().eq_ignore_ascii_case(DOWN_MARKER) {
+      return (&content[..offset], Some(&content[offset + line.len()..]));
+    }
+    offset += line.len();
+  }
+
+  (content, None)
+}
+
+/// FNV-1a hash of a script, which only needs to detect changes and stays stable across builds.
+fn checksum_of(sql: &str) -> String {
+  let hash = sql.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+    (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+  });
+  format!("{hash:016x}")
+}
@@ -0,0 +1,136 @@
+use super::{cipher, error, pragma};
+use napi::{bindgen_prelude::Buffer, Error, Result, Status};
+use napi_derive::napi;
+use rusqlite::{types::Value, Connection, OpenFlags};
+use std::time::Duration;
+
+/// Journal modes accepted by `journalMode`.
+const JOURNAL_MODES: [&str; 6] = ["delete", "truncate", "persist", "memory", "wal", "off"];
+
+/// Levels accepted by `synchronous`.
+const SYNCHRONOUS: [&str; 4] = ["off", "normal", "full", "extra"];
+
+/// Options for `new Database()`, applied when the connection opens.
+#[napi(object)]
+#[derive(Default)]
+pub struct DatabaseOptions {
+  /// Whether to open the database in read-only mode.
+  /// @type {boolean} [readonly=false]
+  pub readonly: Option<bool>,
+
+  /// Whether to fail instead of creating the database file when it does not exist.
+  /// @type {boolean} [fileMustExist=false]
+  pub file_must_exist: Option<bool>,
+
+  /// Milliseconds to wait for a locked database before failing with `SQLITE_BUSY`.
+  /// @type {number} [timeout=5000]
+  pub timeout: Option<u32>,
+
+  /// One of `delete`, `truncate`, `persist`, `memory`, `wal` or `off`.
+  /// @type {string} [journalMode]
+  #[napi(ts_type = "'delete' | 'truncate' | 'persist' | 'memory' | 'wal' | 'off'")]
+  pub journal_mode: Option<String>,
+
+  /// One of `off`, `normal`, `full` or `extra`.
+  /// @type {string} [synchronous]
+  #[napi(ts_type = "'off' | 'normal' | 'full' | 'extra'")]
+  pub synchronous: Option<String>,
+
+  /// Whether to enforce foreign key constraints.
+  /// @type {boolean} [foreignKeys]
+  pub foreign_keys: Option<bool>,
+
+  /// Pages kept in memory when positive, or KiB of memory when negative.
+  /// @type {number} [cacheSize]
+  pub cache_size: Option<i32>,
+
+  /// Whether `loadExtension()` may be called, as extensions run arbitrary native code.
+  /// @type {boolean} [allowExtensions=false]
+  pub allow_extensions: Option<bool>,
+
+  /// 32-byte key encrypting the database file, its journal and its WAL. Every page is sealed
+  /// with AES-256-GCM, so a wrong key or a modified file is reported instead of read.
+  /// Opening an encrypted database requires the same key. Copies made with `backup()`
+  /// and `serialize()` are not encrypted.
+  /// @type {Buffer} [key]
+  pub key: Option<Buffer>,
+}
+
+impl DatabaseOptions {
+  /// Opens the database at `name` and applies the options to the connection.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if an option is invalid or the database cannot be opened.
+  pub fn open(&self, name: &str) -> Result<Connection> {
+    let journal_mode = self
+      .journal_mode
+      .as_deref()
+      .map(|mode| choice("journalMode", mode, &JOURNAL_MODES))
+      .transpose()?;
+
+    let mut flags = OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    if self.readonly.unwrap_or(false) {
+      flags |= OpenFlags::SQLITE_OPEN_READ_ONLY;
+    } else if self.file_must_exist.unwrap_or(false) {
+      flags |= OpenFlags::SQLITE_OPEN_READ_WRITE;
+    } else {
+      flags |= OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE;
+    }
+
+    let conn = self.connect(name, flags)?;
+    if let Some(mode) = journal_mode {
+      pragma::set(&conn, "journal_mode", &Value::Text(mode))?;
+    }
+
+    Ok(conn)
+  }
+
+  /// Opens the database at `name` with `flags` and applies the options that concern
+  /// the connection rather than the file, leaving out `readonly`, `fileMustExist` and `journalMode`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if an option is invalid or the database cannot be opened.
+  pub fn connect(&self, name: &str, flags: OpenFlags) -> Result<Connection> {
+    let synchronous = self
+      .synchronous
+      .as_deref()
+      .map(|level| choice("synchronous", level, &SYNCHRONOUS))
+      .transpose()?;
+
+    let conn = match &self.key {
+      Some(key) => cipher::open(name, flags, cipher::parse_key(key)?)?,
+      None => Connection::open_with_flags(name, flags).map_err(error::sqlite)?,
+    };
+    if let Some(timeout) = self.timeout {
+      conn
+        .busy_timeout(Duration::from_millis(timeout.into()))
+        .map_err(error::sqlite)?;
+    }
+    if let Some(level) = synchronous {
+      pragma::set(&conn, "synchronous", &Value::Text(level))?;
+    }
+    if let Some(enabled) = self.foreign_keys {
+      pragma::set(&conn, "foreign_keys", &Value::Integer(enabled.into()))?;
+    }
+    if let Some(size) = self.cache_size {
+      pragma::set(&conn, "cache_size", &Value::Integer(size.into()))?;
+    }
+
+    Ok(conn)
+  }
+}
+
+/// Checks that `value` is one of `choices`, ignoring case.
+fn choice(option: &str, value: &str, choices: &[&str]) -> Result<String> {
+  let value = value.to_ascii_lowercase();
+  if !choices.contains(&value.as_str()) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid {option} \"{value}\", expected one of {}", choices.join(", ")),
+    ));
+  }
+
+  Ok(value)
+}
@@ -0,0 +1,59 @@
+use super::error;
+use napi::Result;
+use napi_derive::napi;
+use rusqlite::{Connection, Row};
+
+/// The id, the parent id and the detail of a row of `EXPLAIN QUERY PLAN`.
+type Step = (i64, i64, String);
+
+/// A step of a query plan, as reported by `EXPLAIN QUERY PLAN`.
+#[napi(object)]
+pub struct QueryPlan {
+  /// @type {number} id
+  pub id: i64,
+
+  /// What SQLite does at this step, such as `SCAN users` or `SEARCH posts USING INDEX posts_user (user_id=?)`.
+  /// @type {string} detail
+  pub detail: String,
+
+  /// The steps nested in this one, such as the loops of a subquery.
+  /// @type {QueryPlan[]} children
+  pub children: Vec<QueryPlan>,
+}
+
+/// Runs `EXPLAIN QUERY PLAN` on `source` and nests its rows into a tree.
+///
+/// # Errors
+///
+/// Returns an Error if the SQL cannot be compiled.
+pub fn explain(conn: &Connection, source: &str) -> Result<Vec<QueryPlan>> {
+  let mut stmt = conn
+    .prepare(&format!("EXPLAIN QUERY PLAN {source}"))
+    .map_err(error::sqlite)?;
+  // Parameters are left unbound, the plan does not depend on their values.
+  let mut rows = stmt.raw_query();
+  let mut steps = Vec::new();
+  while let Some(row) = rows.next().map_err(error::sqlite)? {
+    steps.push(step(row).map_err(error::sqlite)?);
+  }
+
+  Ok(children(&steps, 0))
+}
+
+/// Reads a row of `EXPLAIN QUERY PLAN`.
+fn step(row: &Row) -> rusqlite::Result<Step> {
+  Ok((row.get(0)?, row.get(1)?, row.get(3)?))
+}
+
+/// Collects the steps whose parent is `parent`, in the order SQLite reported them.
+fn children(steps: &[Step], parent: i64) -> Vec<QueryPlan> {
+  steps
+    .iter()
+    .filter(|(_, step_parent, _)| *step_parent == parent)
+    .map(|(id, _, detail)| QueryPlan {
+      id: *id,
+      detail: detail.clone(),
+      children: children(steps, *id),
+    })
+    .collect()
+}
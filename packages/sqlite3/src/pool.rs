@@ -0,0 +1,243 @@
+use super::{
+  connection::Handle,
+  error,
+  options::DatabaseOptions,
+  row::{Format, Mode},
+  statement, task,
+  value::Params,
+};
+use napi::{bindgen_prelude::FromNapiValue, Env, Error, JsObject, NapiRaw, Result, Status};
+use napi_derive::napi;
+use rusqlite::OpenFlags;
+use std::{cell::Cell, thread};
+
+/// Options for `new Pool()`, which also takes the options of `new Database()` for every
+/// connection, except `readonly` and a `journalMode` other than `wal`.
+#[napi(object)]
+#[derive(Default)]
+pub struct PoolOptions {
+  /// Number of read connections, defaults to the number of CPUs.
+  /// @type {number} [readers]
+  pub readers: Option<u32>,
+
+  /// Whether INTEGER values are returned as `BigInt` instead of numbers.
+  /// @type {boolean} [safeIntegers=false]
+  pub safe_integers: Option<bool>,
+}
+
+/// Usage of the connections of a pool.
+#[napi(object)]
+pub struct PoolStats {
+  /// @type {number} readers - Number of read connections.
+  pub readers: u32,
+
+  /// @type {number} busy - Number of read connections running or waiting for a query.
+  pub busy: u32,
+
+  /// @type {number} idle - Number of read connections without any query.
+  pub idle: u32,
+
+  /// @type {boolean} writerBusy - Whether the write connection is running or waiting for a query.
+  pub writer_busy: bool,
+}
+
+/// A pool of connections to a database file in WAL mode, with several readers
+/// and a single writer, running every query on the libuv thread pool.
+///
+/// Reads go to an idle reader in round-robin order, or queue on the next reader when they
+/// are all busy. Writes queue on the writer and run one after the other, on a single thread
+/// of the libuv pool, so they never keep reads from running. At most `UV_THREADPOOL_SIZE`
+/// queries run at the same time, 4 by default.
+#[napi]
+pub struct Pool {
+  /// @type {string} filename - The absolute path of the database file.
+  /// @readonly
+  #[napi(readonly)]
+  pub filename: String,
+
+  format: Format,
+  readers: Vec<Handle>,
+  writer: Handle,
+  next: Cell<usize>,
+}
+
+#[napi]
+impl Pool {
+  /// Opens the writer and the readers, switching the database to WAL mode.
+  /// @param {string} name - Path of the database file, created if it does not exist.
+  /// @param {PoolOptions & DatabaseOptions} [options]
+  ///
+  /// Example:
+  /// ```js
+  /// const pool = new Pool('./data.db', { readers: 4, timeout: 10000, key });
+  /// const users = await pool.all('SELECT * FROM users WHERE active = ?', [1]);
+  /// ```
+  #[napi(constructor, ts_args_type = "name: string, options?: PoolOptions & DatabaseOptions")]
+  pub fn new(env: Env, name: String, options: Option<JsObject>) -> Result<Self> {
+    let (options, connection) = match options {
+      // SAFETY: both structs read their own properties of the same options object.
+      Some(object) => unsafe {
+        (
+          PoolOptions::from_napi_value(env.raw(), object.raw())?,
+          DatabaseOptions::from_napi_value(env.raw(), object.raw())?,
+        )
+      },
+      None => (PoolOptions::default(), DatabaseOptions::default()),
+    };
+    if connection.readonly.unwrap_or(false) {
+      return Err(Error::new(Status::InvalidArg, "A pool needs a writable database"));
+    }
+    if let Some(mode) = connection
+      .journal_mode
+      .as_deref()
+      .filter(|mode| !mode.eq_ignore_ascii_case("wal"))
+    {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid journalMode \"{mode}\", a pool needs wal"),
+      ));
+    }
+
+    let readers = match options.readers {
+      Some(readers) => readers as usize,
+      None => thread::available_parallelism().map_or(4, |n| n.get()),
+    };
+    if readers == 0 {
+      return Err(Error::new(Status::InvalidArg, "A pool needs at least one reader"));
+    }
+
+    let writer = connection.open(&name)?;
+    let filename = writer.path().unwrap_or_default().to_string();
+    if filename.is_empty() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "A pool needs a database file, in-memory databases cannot be shared",
+      ));
+    }
+
+    let journal_mode: String = writer
+      .query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))
+      .map_err(error::sqlite)?;
+    if !journal_mode.eq_ignore_ascii_case("wal") {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Could not enable WAL mode, the journal mode is \"{journal_mode}\""),
+      ));
+    }
+
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let readers = (0..readers)
+      .map(|_| {
+        let reader = connection.connect(&filename, flags)?;
+        Ok(Handle::new(reader))
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    Ok(Pool {
+      filename,
+      format: Format {
+        mode: Mode::Object,
+        safe_integers: options.safe_integers.unwrap_or(false),
+        tables: None,
+      },
+      readers,
+      writer: Handle::new(writer),
+      next: Cell::new(0),
+    })
+  }
+
+  /// Runs a read query on a reader and returns every row.
+  /// @param {string} sql
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {Promise<unknown[]>} rows
+  #[napi(
+    ts_args_type = "sql: string, params?: unknown[] | Record<string, unknown>",
+    ts_return_type = "Promise<Array<unknown>>"
+  )]
+  pub fn all(&self, env: Env, sql: String, params: Option<Params>) -> Result<JsObject> {
+    let (format, params) = (self.format.clone(), params.unwrap_or_default());
+    task::query(&env, self.reader(), move |conn| {
+      statement::all(conn, &sql, &format, &params)
+    })
+  }
+
+  /// Runs a read query on a reader and returns the first row, or `null` if there is none.
+  /// @param {string} sql
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {Promise<unknown>} row
+  #[napi(
+    ts_args_type = "sql: string, params?: unknown[] | Record<string, unknown>",
+    ts_return_type = "Promise<unknown>"
+  )]
+  pub fn get(&self, env: Env, sql: String, params: Option<Params>) -> Result<JsObject> {
+    let (format, params) = (self.format.clone(), params.unwrap_or_default());
+    task::query(&env, self.reader(), move |conn| {
+      statement::get(conn, &sql, &format, &params)
+    })
+  }
+
+  /// Runs a statement on the writer, after every write queued before it.
+  /// @param {string} sql
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {Promise<RunResult>} info
+  #[napi(
+    ts_args_type = "sql: string, params?: unknown[] | Record<string, unknown>",
+    ts_return_type = "Promise<RunResult>"
+  )]
+  pub fn run(&self, env: Env, sql: String, params: Option<Params>) -> Result<JsObject> {
+    let (format, params) = (self.format.clone(), params.unwrap_or_default());
+    task::query(&env, &self.writer, move |conn| {
+      statement::run(conn, &sql, &format, &params)
+    })
+  }
+
+  /// Executes one or more SQL statements on the writer, after every write queued before them.
+  /// @param {string} sql
+  /// @returns {Promise<void>}
+  #[napi(ts_return_type = "Promise<void>")]
+  pub fn exec(&self, env: Env, sql: String) -> Result<JsObject> {
+    task::query(&env, &self.writer, move |conn| {
+      conn.execute_batch(&sql).map_err(error::sqlite)
+    })
+  }
+
+  /// Returns how many connections are in use.
+  /// @returns {PoolStats} stats
+  #[napi]
+  pub fn stats(&self) -> PoolStats {
+    let busy = self.readers.iter().filter(|reader| reader.is_busy()).count() as u32;
+    PoolStats {
+      readers: self.readers.len() as u32,
+      busy,
+      idle: self.readers.len() as u32 - busy,
+      writer_busy: self.writer.is_busy(),
+    }
+  }
+
+  /// Closes every connection of the pool.
+  /// @returns {undefined}
+  #[napi]
+  pub fn close(&self) -> Result<()> {
+    if self.writer.is_busy() || self.readers.iter().any(Handle::is_busy) {
+      return Err(error::busy());
+    }
+
+    self.readers.iter().try_for_each(Handle::close)?;
+    self.writer.close()
+  }
+}
+
+impl Pool {
+  /// Picks the next idle reader in round-robin order, or the next one if they are all busy.
+  fn reader(&self) -> &Handle {
+    let len = self.readers.len();
+    let start = self.next.get();
+    let index = (0..len)
+      .map(|i| (start + i) % len)
+      .find(|&i| !self.readers[i].is_busy())
+      .unwrap_or(start);
+
+    self.next.set((index + 1) % len);
+    &self.readers[index]
+  }
+}
@@ -0,0 +1,97 @@
+use super::{
+  error,
+  row::{Format, Mode, Row},
+  statement,
+  value::Params,
+};
+use napi::{bindgen_prelude::Either, Error, Result, Status};
+use napi_derive::napi;
+use rusqlite::{types::Value, Connection};
+
+/// Options for `Database.pragma()`.
+#[napi(object)]
+#[derive(Default)]
+pub struct PragmaOptions {
+  /// Whether to return only the first column of the first row instead of every row.
+  /// @type {boolean} [simple=false]
+  pub simple: Option<bool>,
+}
+
+/// Runs `PRAGMA name(value)`, or `PRAGMA name` without a value.
+///
+/// # Errors
+///
+/// Returns an Error if the name is not a valid identifier, the value cannot be
+/// written in SQL or SQLite rejects the pragma.
+pub fn run(
+  conn: &Connection,
+  name: &str,
+  value: Option<&Value>,
+  options: &PragmaOptions,
+  safe_integers: bool,
+) -> Result<Either<Vec<Row>, Option<Row>>> {
+  let sql = sql(name, value)?;
+  let params = Params::default();
+
+  if options.simple.unwrap_or(false) {
+    let format = Format {
+      mode: Mode::Pluck,
+      safe_integers,
+      tables: None,
+    };
+    statement::get(conn, &sql, &format, &params).map(Either::B)
+  } else {
+    let format = Format {
+      mode: Mode::Object,
+      safe_integers,
+      tables: None,
+    };
+    statement::all(conn, &sql, &format, &params).map(Either::A)
+  }
+}
+
+/// Sets a pragma, discarding the rows it returns.
+///
+/// # Errors
+///
+/// Returns an Error if the value cannot be written in SQL or SQLite rejects it.
+pub fn set(conn: &Connection, name: &str, value: &Value) -> Result<()> {
+  let mut stmt = conn.prepare(&sql(name, Some(value))?).map_err(error::sqlite)?;
+  let mut rows = stmt.raw_query();
+  while rows.next().map_err(error::sqlite)?.is_some() {}
+  Ok(())
+}
+
+/// Builds the statement of a pragma, which cannot take bound parameters.
+fn sql(name: &str, value: Option<&Value>) -> Result<String> {
+  let valid = !name.is_empty()
+    && name.split('.').count() <= 2
+    && name.split('.').all(|part| {
+      part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+  if !valid {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid pragma name \"{name}\""),
+    ));
+  }
+
+  Ok(match value {
+    Some(value) => format!("PRAGMA {name}({})", literal(value)?),
+    None => format!("PRAGMA {name}"),
+  })
+}
+
+/// Writes a value as a SQL literal.
+fn literal(value: &Value) -> Result<String> {
+  match value {
+    Value::Integer(i) => Ok(i.to_string()),
+    Value::Real(f) if f.is_finite() => Ok(f.to_string()),
+    Value::Text(s) => Ok(format!("'{}'", s.replace('\'', "''"))),
+    _ => Err(Error::new(
+      Status::InvalidArg,
+      "Pragma values must be numbers, strings or booleans",
+    )),
+  }
+}
@@ -0,0 +1,83 @@
+use super::error;
+use napi::{check_status, sys, Env, JsObject, JsUnknown, NapiRaw, NapiValue, Result};
+use std::{
+  ptr,
+  thread::{self, ThreadId},
+};
+
+/// Property of the holder object of a [`Slot`].
+const VALUE: &str = "value";
+
+/// A strong reference to a JS value, keeping it alive beyond the current call.
+///
+/// It must be dropped on the JS thread, which is where napi runs finalizers.
+pub struct JsRef {
+  env: sys::napi_env,
+  raw: sys::napi_ref,
+  thread: ThreadId,
+}
+
+impl JsRef {
+  pub fn new<T: NapiRaw>(env: &Env, value: &T) -> Result<Self> {
+    let mut raw = ptr::null_mut();
+    check_status!(unsafe { sys::napi_create_reference(env.raw(), value.raw(), 1, &mut raw) })?;
+    Ok(JsRef {
+      env: env.raw(),
+      raw,
+      thread: thread::current().id(),
+    })
+  }
+
+  /// Checks that the current thread is the JS thread that created the reference.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error when called from another thread, such as an async query.
+  pub fn check_thread(&self) -> Result<()> {
+    if thread::current().id() != self.thread {
+      return Err(error::wrong_thread());
+    }
+
+    Ok(())
+  }
+
+  /// Returns the referenced value.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error when called outside the JS thread.
+  pub fn get<T: NapiValue>(&self) -> Result<T> {
+    self.check_thread()?;
+    let mut value = ptr::null_mut();
+    check_status!(unsafe { sys::napi_get_reference_value(self.env, self.raw, &mut value) })?;
+    Ok(unsafe { T::from_raw_unchecked(self.env, value) })
+  }
+}
+
+impl Drop for JsRef {
+  fn drop(&mut self) {
+    unsafe { sys::napi_delete_reference(self.env, self.raw) };
+  }
+}
+
+/// A strong reference to any JS value, primitives included, which plain references
+/// cannot hold before Node-API 10. The value is kept in a holder object.
+pub struct Slot(JsRef);
+
+impl Slot {
+  pub fn new(env: &Env, value: JsUnknown) -> Result<Self> {
+    let mut holder = env.create_object()?;
+    holder.set_named_property(VALUE, value)?;
+    Ok(Slot(JsRef::new(env, &holder)?))
+  }
+
+  /// Returns the referenced value.
+  pub fn get(&self) -> Result<JsUnknown> {
+    self.0.get::<JsObject>()?.get_named_property(VALUE)
+  }
+
+  /// Replaces the referenced value.
+  pub fn set(&self, value: JsUnknown) -> Result<()> {
+    self.0.get::<JsObject>()?.set_named_property(VALUE, value)
+  }
+}
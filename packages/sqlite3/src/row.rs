@@ -0,0 +1,184 @@
+use super::{column, error, value::SqlValue};
+use napi::{
+  bindgen_prelude::{Array, ToNapiValue, TypeName},
+  sys, Env, JsError, JsObject, Result, ValueType,
+};
+use rusqlite::{types::Value, Connection};
+use std::sync::Arc;
+
+/// Key used by expanded rows for columns that do not come from a table.
+const EXPRESSIONS: &str = "$";
+
+/// How rows are converted into JS values.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+  /// An object keyed by column name.
+  #[default]
+  Object,
+  /// An array of values, in column order.
+  Raw,
+  /// The value of the first column only.
+  Pluck,
+  /// An object keyed by table name, each holding an object keyed by column name.
+  Expand,
+}
+
+/// How the rows of a statement are returned to JS.
+#[derive(Clone, Default)]
+pub struct Format {
+  pub mode: Mode,
+  /// Whether INTEGER values are returned as `BigInt` instead of numbers.
+  pub safe_integers: bool,
+  /// The table of each column, read once when a statement is expanded.
+  pub tables: Option<Arc<[String]>>,
+}
+
+/// Reads the table each column of `source` comes from, for expanded rows.
+///
+/// # Errors
+///
+/// Returns an Error if the SQL cannot be compiled.
+pub fn tables(conn: &Connection, source: &str) -> Result<Arc<[String]>> {
+  Ok(
+    column::describe(conn, source)?
+      .into_iter()
+      .map(|c| c.table.unwrap_or_else(|| EXPRESSIONS.to_string()))
+      .collect(),
+  )
+}
+
+/// Describes the rows of a statement, shared by every row it reads.
+pub struct Layout {
+  format: Format,
+  names: Vec<String>,
+  tables: Arc<[String]>,
+}
+
+impl Layout {
+  /// Reads the columns of a prepared statement for the given format.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the tables of expanded rows are unknown or out of date,
+  /// and cannot be read again.
+  pub fn new(conn: &Connection, stmt: &rusqlite::Statement, source: &str, format: &Format) -> Result<Arc<Self>> {
+    let names = stmt.column_names().into_iter().map(String::from).collect::<Vec<_>>();
+    let tables = match (format.mode, &format.tables) {
+      (Mode::Expand, Some(tables)) if tables.len() == names.len() => tables.clone(),
+      // The schema changed since the statement was expanded.
+      (Mode::Expand, _) => tables(conn, source)?,
+      _ => Arc::default(),
+    };
+
+    Ok(Arc::new(Layout {
+      format: format.clone(),
+      names,
+      tables,
+    }))
+  }
+}
+
+/// A row read from a statement, converted into JS according to its layout.
+pub struct Row {
+  layout: Arc<Layout>,
+  values: Vec<Value>,
+}
+
+impl Row {
+  /// Reads the columns of the current row needed by the layout.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if a column cannot be read.
+  pub fn read(layout: &Arc<Layout>, row: &rusqlite::Row) -> Result<Self> {
+    let len = if layout.format.mode == Mode::Pluck {
+      1
+    } else {
+      layout.names.len()
+    };
+    let values = (0..len)
+      .map(|i| row.get::<_, Value>(i))
+      .collect::<rusqlite::Result<_>>()
+      .map_err(error::sqlite)?;
+
+    Ok(Row {
+      layout: layout.clone(),
+      values,
+    })
+  }
+}
+
+impl TypeName for Row {
+  fn type_name() -> &'static str {
+    "Row"
+  }
+
+  fn value_type() -> ValueType {
+    ValueType::Object
+  }
+}
+
+impl ToNapiValue for Row {
+  unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+    let Row { layout, values } = val;
+    let js_env = Env::from_raw(env);
+    let safe_integers = layout.format.safe_integers;
+
+    match layout.format.mode {
+      Mode::Object => {
+        let mut object = js_env.create_object()?;
+        for (name, value) in layout.names.iter().zip(values) {
+          object.set(name, SqlValue::new(value, safe_integers))?;
+        }
+        JsObject::to_napi_value(env, object)
+      }
+      Mode::Raw => {
+        let mut array = js_env.create_array(values.len() as u32)?;
+        for (i, value) in values.into_iter().enumerate() {
+          array.set(i as u32, SqlValue::new(value, safe_integers))?;
+        }
+        Array::to_napi_value(env, array)
+      }
+      Mode::Pluck => {
+        let value = values.into_iter().next().unwrap_or(Value::Null);
+        SqlValue::to_napi_value(env, SqlValue::new(value, safe_integers))
+      }
+      Mode::Expand => {
+        let mut tables: Vec<(&str, JsObject)> = Vec::new();
+        for ((name, table), value) in layout.names.iter().zip(layout.tables.iter()).zip(values) {
+          let index = match tables.iter().position(|(t, _)| t == table) {
+            Some(index) => index,
+            None => {
+              tables.push((table, js_env.create_object()?));
+              tables.len() - 1
+            }
+          };
+          tables[index].1.set(name, SqlValue::new(value, safe_integers))?;
+        }
+
+        let mut object = js_env.create_object()?;
+        for (table, nested) in tables {
+          object.set(table, nested)?;
+        }
+        JsObject::to_napi_value(env, object)
+      }
+    }
+  }
+}
+
+/// A row yielded by an iterator, or the error that stopped it.
+///
+/// Errors are thrown into JS when the value is converted.
+pub struct Step(pub Result<Row>);
+
+impl ToNapiValue for Step {
+  unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+    match val.0 {
+      Ok(row) => Row::to_napi_value(env, row),
+      Err(err) => {
+        JsError::from(err.clone()).throw_into(env);
+        Err(err)
+      }
+    }
+  }
+}
@@ -0,0 +1,63 @@
+use super::error;
+use napi::{bindgen_prelude::Buffer, Error, Result, Status};
+use napi_derive::napi;
+use rusqlite::{ffi, serialize::OwnedData, Connection, DatabaseName};
+use std::ptr::{self, NonNull};
+
+/// Options for `Database.deserialize()`.
+#[napi(object)]
+#[derive(Default)]
+pub struct DeserializeOptions {
+  /// Whether the database is opened in read-only mode.
+  /// @type {boolean} [readonly=false]
+  pub readonly: Option<bool>,
+}
+
+/// Returns the schema called `name`, `main` when missing.
+pub fn schema(name: Option<&str>) -> DatabaseName<'_> {
+  match name {
+    None | Some("main") => DatabaseName::Main,
+    Some("temp") => DatabaseName::Temp,
+    Some(name) => DatabaseName::Attached(name),
+  }
+}
+
+/// Copies the content of the database called `name` (`main` by default) into a Buffer.
+///
+/// # Errors
+///
+/// Returns an Error if the database does not exist or cannot be read.
+pub fn serialize(conn: &Connection, name: Option<&str>) -> Result<Buffer> {
+  let data = conn.serialize(schema(name)).map_err(error::sqlite)?;
+  Ok(data.to_vec().into())
+}
+
+/// Opens an in-memory database holding a copy of the given image.
+///
+/// # Errors
+///
+/// Returns an Error if memory cannot be allocated or the image is not a valid database.
+pub fn deserialize(buffer: &[u8], options: &DeserializeOptions) -> Result<Connection> {
+  let mut conn = Connection::open_in_memory().map_err(error::sqlite)?;
+  if buffer.is_empty() {
+    return Ok(conn);
+  }
+
+  // SQLite takes ownership of the image, so it must be allocated by SQLite.
+  let data = NonNull::new(unsafe { ffi::sqlite3_malloc64(buffer.len() as u64) })
+    .map(|data| unsafe {
+      ptr::copy_nonoverlapping(buffer.as_ptr(), data.as_ptr().cast(), buffer.len());
+      OwnedData::from_raw_nonnull(data.cast(), buffer.len())
+    })
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Out of memory"))?;
+
+  conn
+    .deserialize(DatabaseName::Main, data, options.readonly.unwrap_or(false))
+    .map_err(error::sqlite)?;
+  // Fail early on buffers that are not a database, instead of on the first query.
+  conn
+    .query_row("SELECT count(*) FROM sqlite_schema", [], |_| Ok(()))
+    .map_err(error::sqlite)?;
+
+  Ok(conn)
+}
@@ -0,0 +1,367 @@
+use super::{connection::Handle, error, function::Callback, value::SqlValue};
+use napi::{
+  bindgen_prelude::{Buffer, This},
+  Env, Error, JsFunction, JsString, Result, Status,
+};
+use napi_derive::napi;
+use rusqlite::{ffi, types::Value, Connection};
+use std::{
+  ffi::{c_int, c_void, CStr, CString},
+  ptr, slice,
+};
+
+/// Reads a value of the change under `iter`, given by `sqlite3changeset_old()` and the like.
+type Getter = unsafe extern "C" fn(*mut ffi::sqlite3_changeset_iter, c_int, *mut *mut ffi::sqlite3_value) -> c_int;
+
+/// Turns the result code of a session function into an Error.
+fn check(rc: c_int) -> Result<()> {
+  match rc {
+    ffi::SQLITE_OK => Ok(()),
+    rc => Err(error::sqlite(rusqlite::Error::SqliteFailure(ffi::Error::new(rc), None))),
+  }
+}
+
+/// Copies a value of a change, which is missing for the columns an update leaves out.
+unsafe fn to_value(value: *mut ffi::sqlite3_value) -> Value {
+  if value.is_null() {
+    return Value::Null;
+  }
+  let bytes = |data: *const u8| match data.is_null() {
+    true => Vec::new(),
+    false => slice::from_raw_parts(data, ffi::sqlite3_value_bytes(value) as usize).to_vec(),
+  };
+  match ffi::sqlite3_value_type(value) {
+    ffi::SQLITE_INTEGER => Value::Integer(ffi::sqlite3_value_int64(value)),
+    ffi::SQLITE_FLOAT => Value::Real(ffi::sqlite3_value_double(value)),
+    ffi::SQLITE_TEXT => Value::Text(String::from_utf8_lossy(&bytes(ffi::sqlite3_value_text(value))).into_owned()),
+    ffi::SQLITE_BLOB => Value::Blob(bytes(ffi::sqlite3_value_blob(value).cast())),
+    _ => Value::Null,
+  }
+}
+
+/// Options for `Database.applyChangeset()`.
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
+pub struct ApplyChangesetOptions {
+  /// Decides what to do with a change that conflicts with the database, by returning
+  /// `omit`, `replace` or `abort`. Without it, the first conflict aborts the whole changeset.
+  /// @type {Function} [onConflict]
+  #[napi(ts_type = "(conflict: ChangesetConflict) => 'omit' | 'replace' | 'abort'")]
+  pub on_conflict: Option<JsFunction>,
+}
+
+/// A change of a changeset that cannot be applied as is, given to `onConflict`.
+#[napi(object, object_from_js = false)]
+pub struct ChangesetConflict {
+  /// @type {string} reason - `data` when the row to update or delete has other values,
+  /// `notfound` when it is missing, `conflict` when the row to insert already exists,
+  /// `constraint` and `foreign_key` when the change breaks a constraint.
+  #[napi(ts_type = "'data' | 'notfound' | 'conflict' | 'constraint' | 'foreign_key'")]
+  pub reason: String,
+
+  /// @type {string} table
+  pub table: String,
+
+  /// @type {string} op - `insert`, `update` or `delete`.
+  #[napi(ts_type = "'insert' | 'update' | 'delete'")]
+  pub op: String,
+
+  /// @type {unknown[]} [old] - Values of the row before an update or a delete, `null`
+  /// for the columns an update does not record.
+  #[napi(ts_type = "unknown[]")]
+  pub old: Option<Vec<SqlValue>>,
+
+  /// @type {unknown[]} [new] - Values of the row after an insert or an update, `null`
+  /// for the columns an update leaves unchanged.
+  #[napi(ts_type = "unknown[]")]
+  pub new: Option<Vec<SqlValue>>,
+
+  /// @type {unknown[]} [conflicting] - Values of the row currently in the database,
+  /// for `data` and `conflict`.
+  #[napi(ts_type = "unknown[]")]
+  pub conflicting: Option<Vec<SqlValue>>,
+}
+
+impl ChangesetConflict {
+  /// Describes the conflicting change under `iter`, for a conflict of type `kind`.
+  unsafe fn new(kind: c_int, iter: *mut ffi::sqlite3_changeset_iter, safe_integers: bool) -> Result<Self> {
+    let (mut table, mut columns, mut action, mut indirect) = (ptr::null(), 0, 0, 0);
+    check(ffi::sqlite3changeset_op(
+      iter,
+      &mut table,
+      &mut columns,
+      &mut action,
+      &mut indirect,
+    ))?;
+    let values = |get: Getter| {
+      (0..columns)
+        .map(|i| {
+          let mut value = ptr::null_mut();
+          let value = match get(iter, i, &mut value) {
+            ffi::SQLITE_OK => to_value(value),
+            _ => Value::Null,
+          };
+          SqlValue::new(value, safe_integers)
+        })
+        .collect()
+    };
+
+    Ok(ChangesetConflict {
+      reason: match kind {
+        ffi::SQLITE_CHANGESET_DATA => "data",
+        ffi::SQLITE_CHANGESET_NOTFOUND => "notfound",
+        ffi::SQLITE_CHANGESET_CONFLICT => "conflict",
+        ffi::SQLITE_CHANGESET_CONSTRAINT => "constraint",
+        _ => "foreign_key",
+      }
+      .to_string(),
+      table: CStr::from_ptr(table).to_string_lossy().into_owned(),
+      op: match action {
+        ffi::SQLITE_INSERT => "insert",
+        ffi::SQLITE_UPDATE => "update",
+        _ => "delete",
+      }
+      .to_string(),
+      old: (action != ffi::SQLITE_INSERT).then(|| values(ffi::sqlite3changeset_old)),
+      new: (action != ffi::SQLITE_DELETE).then(|| values(ffi::sqlite3changeset_new)),
+      conflicting: matches!(kind, ffi::SQLITE_CHANGESET_DATA | ffi::SQLITE_CHANGESET_CONFLICT)
+        .then(|| values(ffi::sqlite3changeset_conflict)),
+    })
+  }
+}
+
+/// Records the changes made to the tables of a database, to replay them elsewhere with
+/// `Database.applyChangeset()`. Only tables with a PRIMARY KEY are recorded.
+/// The connection cannot be closed until the session is.
+#[napi]
+pub struct Session {
+  session: Option<*mut ffi::sqlite3_session>,
+  handle: Handle,
+}
+
+impl Session {
+  /// Starts recording the changes of `tables`, or of every table when `None`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the connection is closed or SQLite cannot create the session.
+  pub fn new(handle: Handle, tables: Option<Vec<String>>) -> Result<Self> {
+    let tables = match tables {
+      Some(tables) => Some(
+        tables
+          .into_iter()
+          .map(|table| CString::new(table).map_err(|e| Error::new(Status::InvalidArg, e)))
+          .collect::<Result<Vec<_>>>()?,
+      ),
+      None => None,
+    };
+
+    let session = handle.with(|conn| {
+      // SAFETY: the session is deleted before the connection can close, see `Handle::acquire`.
+      unsafe {
+        let mut session = ptr::null_mut();
+        check(ffi::sqlite3session_create(
+          conn.handle(),
+          c"main".as_ptr(),
+          &mut session,
+        ))?;
+        let attached = match &tables {
+          Some(tables) => tables
+            .iter()
+            .try_for_each(|table| check(ffi::sqlite3session_attach(session, table.as_ptr()))),
+          None => check(ffi::sqlite3session_attach(session, ptr::null())),
+        };
+        if let Err(err) = attached {
+          ffi::sqlite3session_delete(session);
+          return Err(err);
+        }
+        Ok(session)
+      }
+    })?;
+
+    handle.acquire();
+    Ok(Session {
+      session: Some(session),
+      handle,
+    })
+  }
+
+  /// Runs `f` with the session under the connection lock.
+  fn with<T, F>(&mut self, f: F) -> Result<T>
+  where
+    F: FnOnce(*mut ffi::sqlite3_session) -> Result<T>,
+  {
+    let _guard = self.handle.lock();
+    let session = self
+      .session
+      .ok_or_else(|| Error::new(Status::GenericFailure, "The session is closed"))?;
+    f(session)
+  }
+
+  /// Returns the changes recorded so far, written by `sqlite3session_changeset()` or
+  /// `sqlite3session_patchset()`.
+  fn collect(
+    &mut self,
+    write: unsafe extern "C" fn(*mut ffi::sqlite3_session, *mut c_int, *mut *mut c_void) -> c_int,
+  ) -> Result<Buffer> {
+    self.with(|session| unsafe {
+      let (mut len, mut data) = (0, ptr::null_mut());
+      check(write(session, &mut len, &mut data))?;
+      let changes = match data.is_null() {
+        true => Vec::new(),
+        false => slice::from_raw_parts(data.cast::<u8>(), len as usize).to_vec(),
+      };
+      ffi::sqlite3_free(data);
+      Ok(changes.into())
+    })
+  }
+
+  /// Deletes the session and releases the connection.
+  fn finish(&mut self) {
+    if let Some(session) = self.session.take() {
+      let _guard = self.handle.lock();
+      unsafe { ffi::sqlite3session_delete(session) };
+      self.handle.release();
+    }
+  }
+}
+
+impl Drop for Session {
+  fn drop(&mut self) {
+    self.finish();
+  }
+}
+
+#[napi]
+impl Session {
+  /// Returns the changes recorded so far, as a changeset holding the old and new values
+  /// of each changed row. Several changes of the same row are merged into one.
+  /// @returns {Buffer} changeset
+  ///
+  /// Example:
+  /// ```js
+  /// const session = db.createSession(['todos']);
+  /// db.prepare('UPDATE todos SET done = 1 WHERE id = ?').run([id]);
+  /// await fetch('/sync', { method: 'POST', body: session.changeset() });
+  /// ```
+  #[napi]
+  pub fn changeset(&mut self) -> Result<Buffer> {
+    self.collect(ffi::sqlite3session_changeset)
+  }
+
+  /// Returns the changes recorded so far as a patchset, a smaller changeset without the
+  /// old values of updated and deleted rows, so conflicts are only detected on primary keys.
+  /// @returns {Buffer} patchset
+  #[napi]
+  pub fn patchset(&mut self) -> Result<Buffer> {
+    self.collect(ffi::sqlite3session_patchset)
+  }
+
+  /// @type {boolean} isEmpty - Whether no change was recorded.
+  #[napi(getter)]
+  pub fn is_empty(&mut self) -> Result<bool> {
+    self.with(|session| Ok(unsafe { ffi::sqlite3session_isempty(session) } != 0))
+  }
+
+  /// Pauses or resumes recording, for example while applying changes received from elsewhere.
+  /// @param {boolean} [toggle=true]
+  /// @returns {this}
+  #[napi]
+  pub fn enable(&mut self, this: This, toggle: Option<bool>) -> Result<This> {
+    self.with(|session| {
+      unsafe { ffi::sqlite3session_enable(session, c_int::from(toggle.unwrap_or(true))) };
+      Ok(())
+    })?;
+    Ok(this)
+  }
+
+  /// Stops recording and releases the connection. Calling it again does nothing.
+  /// @returns {undefined}
+  #[napi]
+  pub fn close(&mut self) {
+    self.finish();
+  }
+}
+
+/// Applies a changeset or a patchset to the main database, in a single transaction
+/// that is rolled back if a conflict aborts it.
+///
+/// # Errors
+///
+/// Returns an Error if the changeset is invalid, a conflict aborts it, or `onConflict`
+/// throws or returns an unknown resolution.
+pub fn apply(
+  env: &Env,
+  conn: &Connection,
+  changeset: &[u8],
+  options: ApplyChangesetOptions,
+  safe_integers: bool,
+) -> Result<()> {
+  let callback = options
+    .on_conflict
+    .map(|callback| Callback::new(env, &callback, safe_integers))
+    .transpose()?;
+  let len =
+    c_int::try_from(changeset.len()).map_err(|_| Error::new(Status::InvalidArg, "The changeset is too large"))?;
+  let mut context = Conflicts {
+    callback,
+    safe_integers,
+  };
+  check(unsafe {
+    ffi::sqlite3changeset_apply(
+      conn.handle(),
+      len,
+      changeset.as_ptr().cast_mut().cast(),
+      None,
+      Some(on_conflict),
+      ptr::from_mut(&mut context).cast(),
+    )
+  })
+}
+
+/// What `sqlite3changeset_apply()` gives to [`on_conflict`].
+struct Conflicts {
+  callback: Option<Callback>,
+  safe_integers: bool,
+}
+
+/// Called by SQLite for each conflict, with the [`Conflicts`] of the changeset.
+unsafe extern "C" fn on_conflict(
+  context: *mut c_void,
+  kind: c_int,
+  iter: *mut ffi::sqlite3_changeset_iter,
+) -> c_int {
+  let context = &*context.cast::<Conflicts>();
+  let Some(callback) = &context.callback else {
+    return ffi::SQLITE_CHANGESET_ABORT;
+  };
+  // Fails with the error of `onConflict` rather than the abort it causes, see `error::keep`.
+  resolve(callback, kind, iter, context.safe_integers).unwrap_or_else(|err| {
+    error::keep(err);
+    ffi::SQLITE_CHANGESET_ABORT
+  })
+}
+
+/// Asks `onConflict` how to resolve a conflict.
+unsafe fn resolve(
+  callback: &Callback,
+  kind: c_int,
+  iter: *mut ffi::sqlite3_changeset_iter,
+  safe_integers: bool,
+) -> Result<c_int> {
+  let conflict = ChangesetConflict::new(kind, iter, safe_integers)?;
+  let resolution = callback
+    .call(&[callback.convert(conflict)?])?
+    .coerce_to_string()
+    .and_then(JsString::into_utf8)?;
+
+  match resolution.as_str()? {
+    "omit" => Ok(ffi::SQLITE_CHANGESET_OMIT),
+    "replace" => Ok(ffi::SQLITE_CHANGESET_REPLACE),
+    "abort" => Ok(ffi::SQLITE_CHANGESET_ABORT),
+    resolution => Err(Error::new(
+      Status::InvalidArg,
+      format!("Unknown conflict resolution \"{resolution}\", expected omit, replace or abort"),
+    )),
+  }
+}
@@ -0,0 +1,4 @@
+/// Quotes an identifier for use in SQL.
+pub fn quote(name: &str) -> String {
+  format!("\"{}\"", name.replace('"', "\"\""))
+}
@@ -0,0 +1,316 @@
+use super::{
+  cache,
+  column::{self, ColumnInfo},
+  connection::Handle,
+  error,
+  iterator::StatementIterator,
+  plan::{self, QueryPlan},
+  row::{self, Format, Layout, Mode, Row},
+  task,
+  value::Params,
+};
+use napi::{
+  bindgen_prelude::{BigInt, Either, This},
+  Env, Error, JsObject, Result, Status,
+};
+use napi_derive::napi;
+use rusqlite::Connection;
+
+/// Information about the changes made by a statement.
+#[napi(object)]
+pub struct RunResult {
+  /// The number of rows inserted, updated or deleted.
+  /// @type {number} changes
+  pub changes: i64,
+
+  /// The rowid of the last row inserted into the database, a `BigInt` when safe integers are enabled.
+  /// @type {number | bigint} lastInsertRowid
+  pub last_insert_rowid: Either<i64, BigInt>,
+}
+
+/// A prepared statement, created with `Database.prepare(sql)`.
+///
+/// Statements are backed by the connection's prepared statement cache, so running
+/// the same statement again does not compile its SQL twice.
+#[napi]
+pub struct Statement {
+  /// @type {string} source - The SQL text of the statement.
+  /// @readonly
+  #[napi(readonly)]
+  pub source: String,
+
+  /// @type {boolean} reader - Whether the statement returns data.
+  /// @readonly
+  #[napi(readonly)]
+  pub reader: bool,
+
+  /// @type {boolean} readonly - Whether the statement leaves the database unchanged.
+  /// @readonly
+  #[napi(readonly)]
+  pub readonly: bool,
+
+  format: Format,
+  handle: Handle,
+}
+
+impl Statement {
+  /// Compiles the given SQL and stores it in the connection's statement cache.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the connection is closed or the SQL is invalid.
+  pub fn new(handle: Handle, source: String, safe_integers: bool) -> Result<Self> {
+    let (reader, readonly) = handle.with(|conn| {
+      let stmt = cache::compile(conn, &source)?;
+      Ok((stmt.column_count() > 0, stmt.readonly()))
+    })?;
+
+    Ok(Statement {
+      source,
+      reader,
+      readonly,
+      format: Format {
+        mode: Mode::Object,
+        safe_integers,
+        tables: None,
+      },
+      handle,
+    })
+  }
+
+  fn expect_reader(&self) -> Result<()> {
+    if !self.reader {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "This statement does not return data. Use run() instead",
+      ));
+    }
+
+    Ok(())
+  }
+
+  /// Switches to the given mode, or back to objects when disabling the current one.
+  fn set_mode(&mut self, mode: Mode, toggle: Option<bool>) -> Result<()> {
+    if !self.reader {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Row modes are only available for statements that return data",
+      ));
+    }
+
+    if toggle.unwrap_or(true) {
+      self.format.mode = mode;
+    } else if self.format.mode == mode {
+      self.format.mode = Mode::Object;
+    }
+
+    Ok(())
+  }
+}
+
+#[napi]
+impl Statement {
+  /// Executes the statement, discarding any rows it returns.
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {RunResult} info
+  #[napi(ts_args_type = "params?: unknown[] | Record<string, unknown>")]
+  pub fn run(&self, params: Option<Params>) -> Result<RunResult> {
+    let params = params.unwrap_or_default();
+    self.handle.with(|conn| run(conn, &self.source, &self.format, &params))
+  }
+
+  /// Executes the statement on the libuv thread pool, discarding any rows it returns,
+  /// after the async queries made before it. User-defined functions cannot be called from it.
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {Promise<RunResult>} info
+  #[napi(
+    ts_args_type = "params?: unknown[] | Record<string, unknown>",
+    ts_return_type = "Promise<RunResult>"
+  )]
+  pub fn run_async(&self, env: Env, params: Option<Params>) -> Result<JsObject> {
+    let (source, format, params) = (self.source.clone(), self.format.clone(), params.unwrap_or_default());
+    task::query(&env, &self.handle, move |conn| run(conn, &source, &format, &params))
+  }
+
+  /// Executes the statement and returns the first row, or `null` if there is none.
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {unknown} row
+  #[napi(
+    ts_args_type = "params?: unknown[] | Record<string, unknown>",
+    ts_return_type = "unknown"
+  )]
+  pub fn get(&self, params: Option<Params>) -> Result<Option<Row>> {
+    self.expect_reader()?;
+    let params = params.unwrap_or_default();
+    self.handle.with(|conn| get(conn, &self.source, &self.format, &params))
+  }
+
+  /// Executes the statement and returns every row.
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {unknown[]} rows
+  #[napi(
+    ts_args_type = "params?: unknown[] | Record<string, unknown>",
+    ts_return_type = "Array<unknown>"
+  )]
+  pub fn all(&self, params: Option<Params>) -> Result<Vec<Row>> {
+    self.expect_reader()?;
+    let params = params.unwrap_or_default();
+    self.handle.with(|conn| all(conn, &self.source, &self.format, &params))
+  }
+
+  /// Executes the statement on the libuv thread pool and returns every row,
+  /// after the async queries made before it. User-defined functions cannot be called from it.
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {Promise<unknown[]>} rows
+  ///
+  /// Example:
+  /// ```js
+  /// const report = await db.prepare('SELECT * FROM sales WHERE year = ?').allAsync([2024]);
+  /// ```
+  #[napi(
+    ts_args_type = "params?: unknown[] | Record<string, unknown>",
+    ts_return_type = "Promise<Array<unknown>>"
+  )]
+  pub fn all_async(&self, env: Env, params: Option<Params>) -> Result<JsObject> {
+    self.expect_reader()?;
+    let (source, format, params) = (self.source.clone(), self.format.clone(), params.unwrap_or_default());
+    task::query(&env, &self.handle, move |conn| all(conn, &source, &format, &params))
+  }
+
+  /// Executes the statement and returns an iterator that reads rows one at a time.
+  /// The connection cannot be closed until the iterator finishes or is stopped with `return()`.
+  /// @param {unknown[] | Record<string, unknown>} [params]
+  /// @returns {StatementIterator} iterator
+  ///
+  /// Example:
+  /// ```js
+  /// for (const user of db.prepare('SELECT * FROM users').iterate()) {
+  ///   if (user.name === 'Amniel') break;
+  /// }
+  /// ```
+  #[napi(ts_args_type = "params?: unknown[] | Record<string, unknown>")]
+  pub fn iterate(&self, params: Option<Params>) -> Result<StatementIterator> {
+    self.expect_reader()?;
+    StatementIterator::new(
+      self.handle.clone(),
+      &self.source,
+      &self.format,
+      &params.unwrap_or_default(),
+    )
+  }
+
+  /// Returns rows as arrays of values instead of objects.
+  /// @param {boolean} [toggle=true]
+  /// @returns {this}
+  #[napi]
+  pub fn raw(&mut self, this: This, toggle: Option<bool>) -> Result<This> {
+    self.set_mode(Mode::Raw, toggle)?;
+    Ok(this)
+  }
+
+  /// Returns only the value of the first column instead of the whole row.
+  /// @param {boolean} [toggle=true]
+  /// @returns {this}
+  #[napi]
+  pub fn pluck(&mut self, this: This, toggle: Option<bool>) -> Result<This> {
+    self.set_mode(Mode::Pluck, toggle)?;
+    Ok(this)
+  }
+
+  /// Returns rows as objects keyed by table name, each holding the columns of that table.
+  /// Columns that do not come from a table are stored under `$`.
+  /// @param {boolean} [toggle=true]
+  /// @returns {this}
+  ///
+  /// Example:
+  /// ```js
+  /// const row = db.prepare('SELECT users.*, posts.*, 1 AS one FROM users JOIN posts ON ...').expand().get();
+  /// // { users: { id, name }, posts: { id, title }, $: { one: 1 } }
+  /// ```
+  #[napi]
+  pub fn expand(&mut self, this: This, toggle: Option<bool>) -> Result<This> {
+    self.set_mode(Mode::Expand, toggle)?;
+    if self.format.mode == Mode::Expand && self.format.tables.is_none() {
+      let tables = self.handle.with(|conn| row::tables(conn, &self.source))?;
+      self.format.tables = Some(tables);
+    }
+    Ok(this)
+  }
+
+  /// Returns INTEGER values as `BigInt` instead of numbers, which lose precision above 2^53.
+  /// Defaults to the setting of the database when the statement was prepared.
+  /// @param {boolean} [toggle=true]
+  /// @returns {this}
+  #[napi]
+  pub fn safe_integers(&mut self, this: This, toggle: Option<bool>) -> This {
+    self.format.safe_integers = toggle.unwrap_or(true);
+    this
+  }
+
+  /// Describes the columns returned by the statement.
+  /// @returns {ColumnInfo[]} columns
+  #[napi]
+  pub fn columns(&self) -> Result<Vec<ColumnInfo>> {
+    self.expect_reader()?;
+    self.handle.with(|conn| column::describe(conn, &self.source))
+  }
+
+  /// Describes how SQLite runs the statement, as the tree of steps reported by
+  /// `EXPLAIN QUERY PLAN`. The plan does not depend on the values of the parameters.
+  /// @returns {QueryPlan[]} plan
+  ///
+  /// Example:
+  /// ```js
+  /// db.prepare('SELECT * FROM posts WHERE user_id = ?').explain();
+  /// // [{ id: 3, detail: 'SEARCH posts USING INDEX posts_user (user_id=?)', children: [] }]
+  /// ```
+  #[napi]
+  pub fn explain(&self) -> Result<Vec<QueryPlan>> {
+    self.handle.with(|conn| plan::explain(conn, &self.source))
+  }
+}
+
+/// Executes `source`, discarding any rows it returns.
+pub fn run(conn: &Connection, source: &str, format: &Format, params: &Params) -> Result<RunResult> {
+  let mut stmt = cache::prepare(conn, source)?;
+  params.bind(&mut stmt)?;
+  let mut rows = stmt.raw_query();
+  while rows.next().map_err(error::sqlite)?.is_some() {}
+
+  let rowid = conn.last_insert_rowid();
+  Ok(RunResult {
+    changes: conn.changes() as i64,
+    last_insert_rowid: if format.safe_integers {
+      Either::B(BigInt::from(rowid))
+    } else {
+      Either::A(rowid)
+    },
+  })
+}
+
+/// Executes `source` and reads the first row.
+pub fn get(conn: &Connection, source: &str, format: &Format, params: &Params) -> Result<Option<Row>> {
+  let mut stmt = cache::prepare(conn, source)?;
+  let layout = Layout::new(conn, &stmt, source, format)?;
+  params.bind(&mut stmt)?;
+  let mut rows = stmt.raw_query();
+
+  match rows.next().map_err(error::sqlite)? {
+    Some(row) => Ok(Some(Row::read(&layout, row)?)),
+    None => Ok(None),
+  }
+}
+
+/// Executes `source` and reads every row.
+pub fn all(conn: &Connection, source: &str, format: &Format, params: &Params) -> Result<Vec<Row>> {
+  let mut stmt = cache::prepare(conn, source)?;
+  let layout = Layout::new(conn, &stmt, source, format)?;
+  params.bind(&mut stmt)?;
+  let mut rows = stmt.raw_query();
+
+  let mut result = Vec::new();
+  while let Some(row) = rows.next().map_err(error::sqlite)? {
+    result.push(Row::read(&layout, row)?);
+  }
+  Ok(result)
+}
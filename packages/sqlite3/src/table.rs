@@ -0,0 +1,319 @@
+use super::{
+  cache, error,
+  function::{user_error, Callback},
+  reference::JsRef,
+  sql::quote,
+  value,
+};
+use napi::{Env, Error, JsFunction, JsNumber, JsObject, JsUnknown, NapiRaw, NapiValue, Result, Status, ValueType};
+use napi_derive::napi;
+use rusqlite::{
+  ffi,
+  types::Value,
+  vtab::{
+    eponymous_only_module, Context, IndexConstraintOp, IndexInfo, VTab, VTabConfig, VTabConnection, VTabCursor,
+    Values,
+  },
+  Connection,
+};
+use std::{os::raw::c_int, rc::Rc};
+
+/// Most parameters a table can have, as each one is a bit of the index number.
+const MAX_PARAMETERS: usize = 30;
+
+/// Definition of a virtual table whose rows come from a JS generator.
+#[napi(object, object_to_js = false)]
+pub struct TableOptions {
+  /// Names of the columns of the rows.
+  /// @type {string[]} columns
+  pub columns: Vec<String>,
+
+  /// Names of the parameters, hidden columns that receive the arguments of
+  /// `FROM name(...)` or constraints such as `WHERE parameter = ?`.
+  /// Default to `$1`, `$2`... for each argument of `rows`.
+  /// @type {string[]} [parameters]
+  pub parameters: Option<Vec<String>>,
+
+  /// Called with the parameters for each scan of the table, `undefined` for those not given,
+  /// and returns an iterator of rows, each one an array of values or an object keyed by column.
+  /// @type {Function} rows
+  #[napi(ts_type = "(...parameters: any[]) => Iterator<unknown[] | Record<string, unknown>>")]
+  pub rows: JsFunction,
+
+  /// Whether the table can only be used from top-level SQL,
+  /// and not from views, triggers or schema structures.
+  /// @type {boolean} [directOnly=false]
+  pub direct_only: Option<bool>,
+}
+
+/// A table as defined from JS, shared by the module and the instances SQLite connects,
+/// which are only used under the connection lock.
+struct Definition {
+  name: String,
+  columns: Vec<String>,
+  parameters: Vec<String>,
+  direct_only: bool,
+  rows: Callback,
+}
+
+/// Registers `name` as an eponymous virtual table, usable as `FROM name` or `FROM name(...)`
+/// without `CREATE VIRTUAL TABLE`, replacing any table defined with the same name.
+///
+/// # Errors
+///
+/// Returns an Error if the parameters cannot be named, the generator cannot be referenced
+/// or SQLite rejects the module.
+pub fn create(
+  env: &Env,
+  conn: &Connection,
+  name: String,
+  options: TableOptions,
+  safe_integers: bool,
+) -> Result<()> {
+  let parameters = match options.parameters {
+    Some(parameters) => parameters,
+    None => {
+      let object = unsafe { JsObject::from_raw_unchecked(env.raw(), options.rows.raw()) };
+      let length = object.get_named_property::<JsNumber>("length")?.get_uint32()?;
+      (1..=length).map(|i| format!("${i}")).collect()
+    }
+  };
+  if parameters.len() > MAX_PARAMETERS {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Tables cannot have more than {MAX_PARAMETERS} parameters"),
+    ));
+  }
+
+  let definition = Definition {
+    columns: options.columns,
+    parameters,
+    direct_only: options.direct_only.unwrap_or(false),
+    rows: Callback::new(env, &options.rows, safe_integers)?,
+    name,
+  };
+  conn
+    .create_module(
+      &definition.name.clone(),
+      eponymous_only_module::<GeneratorTable>(),
+      Some(Rc::new(definition)),
+    )
+    .map_err(error::sqlite)?;
+  // Cached statements keep using the table they were prepared with.
+  cache::flush(conn);
+  Ok(())
+}
+
+/// An instance of a table, connected by SQLite when a statement uses it.
+#[repr(C)]
+struct GeneratorTable {
+  base: ffi::sqlite3_vtab,
+  definition: Rc<Definition>,
+}
+
+unsafe impl<'vtab> VTab<'vtab> for GeneratorTable {
+  type Aux = Rc<Definition>;
+  type Cursor = GeneratorCursor;
+
+  fn connect(
+    db: &mut VTabConnection,
+    aux: Option<&Rc<Definition>>,
+    _args: &[&[u8]],
+  ) -> rusqlite::Result<(String, Self)> {
+    let definition = aux
+      .cloned()
+      .ok_or_else(|| rusqlite::Error::ModuleError("Missing table definition".into()))?;
+    if definition.direct_only {
+      db.config(VTabConfig::DirectOnly)?;
+    }
+
+    let columns = definition
+      .columns
+      .iter()
+      .map(|column| quote(column))
+      .chain(
+        definition
+          .parameters
+          .iter()
+          .map(|parameter| format!("{} HIDDEN", quote(parameter))),
+      )
+      .collect::<Vec<_>>();
+    Ok((
+      format!("CREATE TABLE x({})", columns.join(", ")),
+      GeneratorTable {
+        base: ffi::sqlite3_vtab::default(),
+        definition,
+      },
+    ))
+  }
+
+  /// Passes the parameters that have an `=` constraint to the generator, as the bits of the index number.
+  fn best_index(&self, info: &mut IndexInfo) -> rusqlite::Result<()> {
+    let first = self.definition.columns.len() as c_int;
+    let mut given = vec![None; self.definition.parameters.len()];
+    let mut unusable = 0;
+    for (i, constraint) in info.constraints().enumerate() {
+      let Some(parameter) = constraint.column().checked_sub(first).filter(|&column| column >= 0) else {
+        continue;
+      };
+      if !constraint.is_usable() {
+        unusable |= 1 << parameter;
+      } else if constraint.operator() == IndexConstraintOp::SQLITE_INDEX_CONSTRAINT_EQ {
+        given[parameter as usize] = Some(i);
+      }
+    }
+
+    let mut idx_num = 0;
+    let mut argv_index = 0;
+    for (parameter, constraint) in given.iter().enumerate() {
+      let Some(constraint) = *constraint else {
+        continue;
+      };
+      idx_num |= 1 << parameter;
+      argv_index += 1;
+      let mut usage = info.constraint_usage(constraint);
+      usage.set_argv_index(argv_index);
+      usage.set_omit(true);
+    }
+    // A parameter given by a join must be used, so SQLite picks another plan.
+    if (unusable & !idx_num) != 0 {
+      return Err(rusqlite::Error::SqliteFailure(
+        ffi::Error::new(ffi::SQLITE_CONSTRAINT),
+        None,
+      ));
+    }
+
+    info.set_idx_num(idx_num);
+    info.set_estimated_cost(1_000_000.0 / f64::from(1 << argv_index));
+    Ok(())
+  }
+
+  fn open(&'vtab mut self) -> rusqlite::Result<GeneratorCursor> {
+    Ok(GeneratorCursor {
+      base: ffi::sqlite3_vtab_cursor::default(),
+      definition: self.definition.clone(),
+      iterator: None,
+      arguments: Vec::new(),
+      row: Vec::new(),
+      rowid: 0,
+    })
+  }
+}
+
+/// A scan of a table, reading the rows of one iterator returned by the generator.
+#[repr(C)]
+struct GeneratorCursor {
+  base: ffi::sqlite3_vtab_cursor,
+  definition: Rc<Definition>,
+  iterator: Option<JsRef>,
+  arguments: Vec<Option<Value>>,
+  row: Vec<Value>,
+  rowid: i64,
+}
+
+impl GeneratorCursor {
+  /// Calls the generator with the parameters given by `best_index`, `undefined` for the others.
+  fn start(&mut self, idx_num: c_int, args: &Values<'_>) -> Result<()> {
+    let rows = &self.definition.rows;
+    let mut values = args.iter();
+    self.arguments = (0..self.definition.parameters.len())
+      .map(|parameter| match idx_num & (1 << parameter) {
+        0 => None,
+        _ => values.next().map(Value::from),
+      })
+      .collect();
+    let arguments = self
+      .arguments
+      .iter()
+      .map(|argument| match argument {
+        Some(value) => rows.argument(value.clone()),
+        None => rows.convert(()),
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    let iterator = rows.call(&arguments)?;
+    if iterator.get_type()? != ValueType::Object {
+      return Err(self.invalid("rows() must return an iterator"));
+    }
+    self.iterator = Some(JsRef::new(rows.env(), &iterator)?);
+    self.rowid = 0;
+    self.advance()
+  }
+
+  /// Reads the next row of the iterator, releasing it once it is done.
+  fn advance(&mut self) -> Result<()> {
+    let Some(iterator) = &self.iterator else {
+      return Ok(());
+    };
+    let iterator = iterator.get::<JsObject>()?;
+    let result: JsObject = iterator
+      .get_named_property::<JsFunction>("next")?
+      .call_without_args(Some(&iterator))?
+      .coerce_to_object()?;
+    if result
+      .get_named_property::<JsUnknown>("done")?
+      .coerce_to_bool()?
+      .get_value()?
+    {
+      self.iterator = None;
+      self.row.clear();
+      return Ok(());
+    }
+
+    let row = result.get_named_property::<JsUnknown>("value")?;
+    if row.get_type()? != ValueType::Object {
+      return Err(self.invalid("Rows must be arrays or objects"));
+    }
+    let row = unsafe { row.cast::<JsObject>() };
+    let is_array = row.is_array()?;
+    self.row = self
+      .definition
+      .columns
+      .iter()
+      .enumerate()
+      .map(|(i, column)| {
+        if is_array {
+          row.get_element::<JsUnknown>(i as u32)
+        } else {
+          row.get_named_property::<JsUnknown>(column)
+        }
+      })
+      .map(|value| value.and_then(value::to_value))
+      .collect::<Result<_>>()?;
+    self.rowid += 1;
+    Ok(())
+  }
+
+  fn invalid(&self, message: &str) -> Error {
+    Error::new(
+      Status::InvalidArg,
+      format!("Invalid table \"{}\": {message}", self.definition.name),
+    )
+  }
+}
+
+unsafe impl VTabCursor for GeneratorCursor {
+  fn filter(&mut self, idx_num: c_int, _idx_str: Option<&str>, args: &Values<'_>) -> rusqlite::Result<()> {
+    self.start(idx_num, args).map_err(user_error)
+  }
+
+  fn next(&mut self) -> rusqlite::Result<()> {
+    self.advance().map_err(user_error)
+  }
+
+  fn eof(&self) -> bool {
+    self.iterator.is_none()
+  }
+
+  fn column(&self, ctx: &mut Context, i: c_int) -> rusqlite::Result<()> {
+    let i = i as usize;
+    match i.checked_sub(self.definition.columns.len()) {
+      None => ctx.set_result(&self.row[i]),
+      Some(parameter) => ctx.set_result(&self.arguments[parameter]),
+    }
+  }
+
+  fn rowid(&self) -> rusqlite::Result<i64> {
+    Ok(self.rowid)
+  }
+}
@@ -0,0 +1,71 @@
+use super::{connection::Handle, error};
+use napi::{
+  bindgen_prelude::{ToNapiValue, Undefined},
+  Env, JsObject, Result, Task,
+};
+use rusqlite::Connection;
+
+/// An async query waiting for its turn on a connection, which settles its promise once it ran.
+pub type Job = Box<dyn FnOnce(Result<&Connection>) + Send>;
+
+/// Queues `job` to run on the libuv thread pool with the connection, returning a promise
+/// resolving to the value it returns.
+///
+/// The queries of a connection run one at a time in the order they were queued, so a
+/// connection never holds more than one thread of the pool, and none waits for its lock.
+/// The connection cannot be closed until they finish, and synchronous calls throw instead
+/// of blocking the event loop until then. JS functions registered on the connection return
+/// an error if the query calls them.
+///
+/// # Errors
+///
+/// Returns an Error if the connection is closed or the task cannot be started.
+pub fn query<T, F>(env: &Env, handle: &Handle, job: F) -> Result<JsObject>
+where
+  T: ToNapiValue + Send + 'static,
+  F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+{
+  // A deferred that is never settled keeps the process alive.
+  if !handle.is_open() {
+    return Err(error::not_open());
+  }
+
+  let (deferred, promise) = env.create_deferred()?;
+  let settled = handle.clone();
+  let job: Job = Box::new(move |conn| {
+    let result = conn.and_then(job);
+    let failure = result.as_ref().err().and_then(error::take);
+    deferred.resolve(move |env| {
+      settled.settle();
+      result.map_err(|err| error::reject(&env, err, failure))
+    });
+  });
+
+  if handle.enqueue(job) {
+    env.spawn(QueryTask { handle: handle.clone() })?;
+  }
+  Ok(promise)
+}
+
+/// Runs the async queries queued on a connection on the libuv thread pool, until none is left.
+pub struct QueryTask {
+  handle: Handle,
+}
+
+impl Task for QueryTask {
+  type Output = ();
+  type JsValue = Undefined;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    while let Some(job) = self.handle.dequeue() {
+      let guard = self.handle.lock();
+      let slot = guard.borrow();
+      job(slot.as_ref().ok_or_else(error::not_open));
+    }
+    Ok(())
+  }
+
+  fn resolve(&mut self, _: Env, _: Self::Output) -> Result<Self::JsValue> {
+    Ok(())
+  }
+}
@@ -0,0 +1,119 @@
+use super::{connection::Handle, error, hooks::threadsafe};
+use napi::{
+  threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+  Env, JsFunction, Result,
+};
+use napi_derive::napi;
+use rusqlite::ffi;
+use std::{
+  ffi::{c_char, c_int, c_uint, c_void, CStr},
+  ptr,
+};
+
+/// A statement execution, given to `profile` listeners.
+#[napi(object)]
+pub struct ProfileEvent {
+  /// @type {string} sql - The SQL text of the statement, without the values of its parameters.
+  pub sql: String,
+
+  /// @type {number} duration - Milliseconds the statement took to run.
+  pub duration: f64,
+}
+
+/// The listeners of `Database.trace()` and `Database.profile()`, called from whichever
+/// thread runs the statement, async queries included.
+#[derive(Clone, Default)]
+pub struct Tracer {
+  trace: Option<ThreadsafeFunction<String, ErrorStrategy::Fatal>>,
+  profile: Option<ThreadsafeFunction<ProfileEvent, ErrorStrategy::Fatal>>,
+}
+
+impl Tracer {
+  /// Replaces the listener receiving the SQL of each statement that starts running.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the listener cannot be referenced or the connection is closed.
+  pub fn trace(&mut self, env: &Env, handle: &Handle, listener: Option<&JsFunction>) -> Result<()> {
+    self.trace = listener
+      .map(|listener| threadsafe(env, listener, |sql| vec![sql]))
+      .transpose()?;
+    self.install(handle)
+  }
+
+  /// Replaces the listener receiving the SQL and the duration of each statement that finishes.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the listener cannot be referenced or the connection is closed.
+  pub fn profile(&mut self, env: &Env, handle: &Handle, listener: Option<&JsFunction>) -> Result<()> {
+    self.profile = listener
+      .map(|listener| threadsafe(env, listener, |event| vec![event]))
+      .transpose()?;
+    self.install(handle)
+  }
+
+  /// Registers the listeners with SQLite, or removes the trace callback when there are none.
+  fn install(&self, handle: &Handle) -> Result<()> {
+    let mut mask = 0;
+    if self.trace.is_some() {
+      mask |= ffi::SQLITE_TRACE_STMT;
+    }
+    if self.profile.is_some() {
+      mask |= ffi::SQLITE_TRACE_PROFILE;
+    }
+    let tracer = (mask != 0).then(|| Box::new(self.clone()));
+
+    handle.with(|conn| {
+      let data = tracer
+        .as_deref()
+        .map_or(ptr::null_mut(), |tracer| tracer as *const Tracer as *mut c_void);
+      // SAFETY: see `Handle::set_tracer`.
+      let code =
+        unsafe { ffi::sqlite3_trace_v2(conn.handle(), mask as c_uint, tracer.is_some().then_some(call), data) };
+      if code != ffi::SQLITE_OK {
+        return Err(error::sqlite(rusqlite::Error::SqliteFailure(
+          ffi::Error::new(code),
+          None,
+        )));
+      }
+      Ok(())
+    })?;
+    handle.set_tracer(tracer);
+    Ok(())
+  }
+}
+
+/// Called by SQLite with the tracer given to `sqlite3_trace_v2`.
+unsafe extern "C" fn call(event: c_uint, data: *mut c_void, stmt: *mut c_void, x: *mut c_void) -> c_int {
+  let tracer = unsafe { &*(data as *const Tracer) };
+  match event as c_int {
+    // The SQL of statements run by triggers is reported as a `-- TRIGGER name` comment.
+    ffi::SQLITE_TRACE_STMT => {
+      if let Some(trace) = &tracer.trace {
+        let sql = unsafe { to_string(x as *const c_char) };
+        trace.call(sql, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }
+    ffi::SQLITE_TRACE_PROFILE => {
+      if let Some(profile) = &tracer.profile {
+        let sql = unsafe { to_string(ffi::sqlite3_sql(stmt as *mut ffi::sqlite3_stmt)) };
+        let nanoseconds = unsafe { *(x as *const i64) };
+        let event = ProfileEvent {
+          sql,
+          duration: nanoseconds as f64 / 1e6,
+        };
+        profile.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }
+    _ => {}
+  }
+  0
+}
+
+unsafe fn to_string(text: *const c_char) -> String {
+  if text.is_null() {
+    return String::new();
+  }
+  unsafe { CStr::from_ptr(text) }.to_string_lossy().into_owned()
+}
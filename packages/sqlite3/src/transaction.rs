@@ -0,0 +1,133 @@
+use super::{connection::Handle, error, reference::JsRef};
+use napi::{CallContext, Env, Error, JsFunction, JsObject, JsUnknown, NapiRaw, NapiValue, Result, Status};
+use rusqlite::Connection;
+use std::{iter, rc::Rc};
+
+/// Savepoint used when a transaction function runs inside another transaction.
+const SAVEPOINT: &str = "\"_transaction\"";
+
+/// How a transaction acquires its locks, see <https://www.sqlite.org/lang_transaction.html>.
+#[derive(Clone, Copy)]
+enum Behavior {
+  Default,
+  Deferred,
+  Immediate,
+  Exclusive,
+}
+
+/// Variants attached to every transaction function.
+const VARIANTS: [Behavior; 3] = [Behavior::Deferred, Behavior::Immediate, Behavior::Exclusive];
+
+impl Behavior {
+  fn name(self) -> &'static str {
+    match self {
+      Behavior::Default => "transaction",
+      Behavior::Deferred => "deferred",
+      Behavior::Immediate => "immediate",
+      Behavior::Exclusive => "exclusive",
+    }
+  }
+
+  fn begin(self) -> &'static str {
+    match self {
+      Behavior::Default => "BEGIN",
+      Behavior::Deferred => "BEGIN DEFERRED",
+      Behavior::Immediate => "BEGIN IMMEDIATE",
+      Behavior::Exclusive => "BEGIN EXCLUSIVE",
+    }
+  }
+}
+
+/// Wraps `callback` in a function running it inside a transaction, with its
+/// `deferred`, `immediate` and `exclusive` variants attached as properties.
+///
+/// # Errors
+///
+/// Returns an Error if the JS functions cannot be created.
+pub fn create(env: &Env, handle: &Handle, callback: &JsFunction) -> Result<JsFunction> {
+  let callback = Rc::new(JsRef::new(env, callback)?);
+  let default = wrap(env, handle.clone(), callback.clone(), Behavior::Default)?;
+  let variants = VARIANTS
+    .iter()
+    .map(|&behavior| wrap(env, handle.clone(), callback.clone(), behavior))
+    .collect::<Result<Vec<_>>>()?;
+
+  for function in iter::once(&default).chain(&variants) {
+    let mut object = unsafe { JsObject::from_raw_unchecked(env.raw(), function.raw()) };
+    for (behavior, variant) in VARIANTS.iter().zip(&variants) {
+      object.set_named_property(behavior.name(), variant)?;
+    }
+  }
+
+  Ok(default)
+}
+
+fn wrap(env: &Env, handle: Handle, callback: Rc<JsRef>, behavior: Behavior) -> Result<JsFunction> {
+  env.create_function_from_closure(behavior.name(), move |ctx: CallContext| {
+    let this = ctx.this_unchecked::<JsObject>();
+    let args = (0..ctx.length)
+      .map(|i| ctx.get::<JsUnknown>(i))
+      .collect::<Result<Vec<_>>>()?;
+
+    let nested = handle.with(|conn| Ok(!conn.is_autocommit()))?;
+    let (begin, commit, rollback) = if nested {
+      (
+        format!("SAVEPOINT {SAVEPOINT}"),
+        format!("RELEASE {SAVEPOINT}"),
+        format!("ROLLBACK TO {SAVEPOINT}; RELEASE {SAVEPOINT}"),
+      )
+    } else {
+      (
+        behavior.begin().to_string(),
+        "COMMIT".to_string(),
+        "ROLLBACK".to_string(),
+      )
+    };
+
+    execute(&handle, &begin)?;
+    let result = callback
+      .get::<JsFunction>()?
+      .call(Some(&this), &args)
+      .and_then(|result| {
+        if result.is_promise()? {
+          return Err(Error::new(
+            Status::InvalidArg,
+            "Transaction function cannot return a promise",
+          ));
+        }
+        execute(&handle, &commit)?;
+        Ok(result)
+      });
+
+    if result.is_err() && handle.with(|conn| Ok(!conn.is_autocommit()))? {
+      execute(&handle, &rollback)?;
+    }
+    result
+  })
+}
+
+/// Runs `f` inside the savepoint `name`, which also works inside a transaction.
+/// The savepoint is released if `f` succeeds, and rolled back otherwise.
+///
+/// # Errors
+///
+/// Returns an Error if the savepoint cannot be started or released, or the Error of `f`.
+pub fn savepoint<T>(conn: &Connection, name: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+  conn
+    .execute_batch(&format!("SAVEPOINT {name}"))
+    .map_err(error::sqlite)?;
+  let result = f().and_then(|value| {
+    conn.execute_batch(&format!("RELEASE {name}")).map_err(error::sqlite)?;
+    Ok(value)
+  });
+  if result.is_err() {
+    // Fails when SQLite already rolled the transaction back, which is what we want anyway.
+    let _ = conn.execute_batch(&format!("ROLLBACK TO {name}; RELEASE {name}"));
+  }
+  result
+}
+
+#[inline]
+fn execute(handle: &Handle, sql: &str) -> Result<()> {
+  handle.with(|conn| conn.execute_batch(sql).map_err(error::sqlite))
+}
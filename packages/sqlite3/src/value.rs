@@ -0,0 +1,200 @@
+use super::error;
+use napi::{
+  bindgen_prelude::{BigInt, Buffer, FromNapiValue, Null, ToNapiValue, TypeName, ValidateNapiValue},
+  sys, Error, JsBigInt, JsBoolean, JsBuffer, JsDate, JsFunction, JsNumber, JsObject, JsString, JsUnknown,
+  NapiValue, Result, Status, ValueType,
+};
+use rusqlite::types::Value;
+use std::collections::HashMap;
+
+/// Largest integer a JS number can represent exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// A value read from SQLite, converted into its JS counterpart.
+pub struct SqlValue {
+  value: Value,
+  safe_integers: bool,
+}
+
+impl SqlValue {
+  /// Wraps a value, returning INTEGER values as `BigInt` when `safe_integers` is set.
+  pub fn new(value: Value, safe_integers: bool) -> Self {
+    SqlValue { value, safe_integers }
+  }
+}
+
+impl ToNapiValue for SqlValue {
+  unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+    match val.value {
+      Value::Null => Null::to_napi_value(env, Null),
+      Value::Integer(i) if val.safe_integers => BigInt::to_napi_value(env, BigInt::from(i)),
+      Value::Integer(i) => i64::to_napi_value(env, i),
+      Value::Real(f) => f64::to_napi_value(env, f),
+      Value::Text(s) => String::to_napi_value(env, s),
+      Value::Blob(b) => Buffer::to_napi_value(env, b.into()),
+    }
+  }
+}
+
+/// Parameters bound to a statement, given from JS as an array of positional values
+/// or as an object of named values (`:name`, `@name` or `$name` in SQL).
+pub enum Params {
+  Positional(Vec<Value>),
+  Named(HashMap<String, Value>),
+}
+
+impl Default for Params {
+  fn default() -> Self {
+    Params::Positional(Vec::new())
+  }
+}
+
+impl Params {
+  /// Binds every parameter of the statement, replacing previous bindings.
+  ///
+  /// # Errors
+  ///
+  /// Returns an Error if the number of positional values does not match the statement,
+  /// or a named parameter is missing from the object.
+  pub fn bind(&self, stmt: &mut rusqlite::Statement) -> Result<()> {
+    stmt.clear_bindings();
+    let count = stmt.parameter_count();
+
+    match self {
+      Params::Positional(values) => {
+        if values.len() != count {
+          return Err(Error::new(
+            Status::InvalidArg,
+            format!("Expected {count} parameters, got {}", values.len()),
+          ));
+        }
+        for (i, value) in values.iter().enumerate() {
+          stmt.raw_bind_parameter(i + 1, value).map_err(error::sqlite)?;
+        }
+      }
+      Params::Named(values) => {
+        for i in 1..=count {
+          let name = match stmt.parameter_name(i) {
+            Some(name) if !name.starts_with('?') => name[1..].to_string(),
+            _ => {
+              return Err(Error::new(
+                Status::InvalidArg,
+                "Statement uses anonymous parameters, pass them as an array",
+              ))
+            }
+          };
+          let value = values
+            .get(&name)
+            .ok_or_else(|| Error::new(Status::InvalidArg, format!("Missing named parameter \"{name}\"")))?;
+          stmt.raw_bind_parameter(i, value).map_err(error::sqlite)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl TypeName for Params {
+  fn type_name() -> &'static str {
+    "Params"
+  }
+
+  fn value_type() -> ValueType {
+    ValueType::Object
+  }
+}
+
+impl ValidateNapiValue for Params {}
+
+impl FromNapiValue for Params {
+  unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+    let value = JsUnknown::from_raw(env, napi_val)?;
+    if value.get_type()? != ValueType::Object || value.is_buffer()? || value.is_date()? {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Parameters must be given as an array or an object",
+      ));
+    }
+
+    let object = value.cast::<JsObject>();
+    if object.is_array()? {
+      let len = object.get_array_length()?;
+      let mut values = Vec::with_capacity(len as usize);
+      for i in 0..len {
+        let value = to_value(object.get_element::<JsUnknown>(i)?)
+          .map_err(|e| Error::new(e.status, format!("Parameter {}: {}", i + 1, e.reason)))?;
+        values.push(value);
+      }
+      return Ok(Params::Positional(values));
+    }
+
+    let keys = object.get_property_names()?;
+    let len = keys.get_array_length()?;
+    let mut values = HashMap::with_capacity(len as usize);
+    for i in 0..len {
+      let key = keys.get_element::<JsString>(i)?.into_utf8()?.into_owned()?;
+      let value = to_value(object.get_named_property::<JsUnknown>(&key)?)
+        .map_err(|e| Error::new(e.status, format!("Parameter \"{key}\": {}", e.reason)))?;
+      values.insert(key, value);
+    }
+
+    Ok(Params::Named(values))
+  }
+}
+
+/// Converts a JS value into a SQLite value.
+///
+/// Booleans are stored as `0`/`1`, `Date` objects as ISO-8601 text and
+/// `Buffer` objects as blobs.
+///
+/// # Errors
+///
+/// Returns an Error if the JS type cannot be stored in SQLite.
+pub fn to_value(value: JsUnknown) -> Result<Value> {
+  match value.get_type()? {
+    ValueType::Null | ValueType::Undefined => Ok(Value::Null),
+    ValueType::Boolean => Ok(Value::Integer(unsafe { value.cast::<JsBoolean>() }.get_value()? as i64)),
+    ValueType::Number => {
+      let n = unsafe { value.cast::<JsNumber>() }.get_double()?;
+      if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER {
+        Ok(Value::Integer(n as i64))
+      } else {
+        Ok(Value::Real(n))
+      }
+    }
+    ValueType::BigInt => {
+      let (n, lossless) = unsafe { value.cast::<JsBigInt>() }.get_i64()?;
+      if !lossless {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "BigInt is out of range for a 64-bit integer",
+        ));
+      }
+      Ok(Value::Integer(n))
+    }
+    ValueType::String => Ok(Value::Text(
+      unsafe { value.cast::<JsString>() }.into_utf8()?.into_owned()?,
+    )),
+    ValueType::Object if value.is_buffer()? => {
+      Ok(Value::Blob(unsafe { value.cast::<JsBuffer>() }.into_value()?.to_vec()))
+    }
+    ValueType::Object if value.is_date()? => {
+      if unsafe { value.cast::<JsDate>() }.value_of()?.is_nan() {
+        return Err(Error::new(Status::InvalidArg, "Invalid Date"));
+      }
+      let date = unsafe { value.cast::<JsObject>() };
+      let iso = date
+        .get_named_property::<JsFunction>("toISOString")?
+        .call_without_args(Some(&date))?
+        .coerce_to_string()?
+        .into_utf8()?
+        .into_owned()?;
+      Ok(Value::Text(iso))
+    }
+    other => Err(Error::new(
+      Status::InvalidArg,
+      format!("SQLite cannot bind values of type \"{other}\""),
+    )),
+  }
+}
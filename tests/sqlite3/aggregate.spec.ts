@@ -0,0 +1,81 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+type Average = { sum: number; weight: number };
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec(`
+    CREATE TABLE orders (id INTEGER PRIMARY KEY, customer TEXT, price REAL, quantity INTEGER);
+    INSERT INTO orders (customer, price, quantity) VALUES
+      ('amniel', 10, 1), ('amniel', 20, 3), ('rust', 5, 2), ('rust', 15, 2);
+  `);
+  return db;
+}
+
+test('aggregate', (t) => {
+  const db = setup();
+  t.is(
+    db.aggregate('weighted_avg', {
+      start: () => ({ sum: 0, weight: 0 }),
+      step: (acc: Average, value: number, weight: number) => {
+        acc.sum += value * weight;
+        acc.weight += weight;
+      },
+      result: (acc: Average) => (acc.weight ? acc.sum / acc.weight : null),
+    }),
+    db,
+  );
+
+  t.deepEqual(
+    db.prepare('SELECT customer, weighted_avg(price, quantity) AS avg FROM orders GROUP BY customer').all(),
+    [
+      { customer: 'amniel', avg: 17.5 },
+      { customer: 'rust', avg: 10 },
+    ],
+  );
+  t.is(db.prepare('SELECT weighted_avg(price, quantity) FROM orders WHERE id > 10').pluck().get(), null);
+  db.close();
+});
+
+test('start value', (t) => {
+  const db = setup();
+  db.aggregate('total', { start: 0, step: (acc: number, value: number) => acc + value });
+  db.aggregate('list', { step: (acc: string | null, value: string) => (acc ? `${acc},${value}` : value) });
+
+  t.is(db.prepare('SELECT total(quantity) FROM orders').pluck().get(), 8);
+  t.is(db.prepare('SELECT total(quantity) FROM orders WHERE id > 10').pluck().get(), 0);
+  t.is(db.prepare('SELECT list(customer) FROM orders WHERE quantity = 2').pluck().get(), 'rust,rust');
+  db.close();
+});
+
+test('window', (t) => {
+  const db = setup();
+  db.aggregate('running', {
+    start: 0,
+    step: (acc: number, value: number) => acc + value,
+    inverse: (acc: number, value: number) => acc - value,
+  });
+
+  const sql = 'SELECT running(quantity) OVER (ORDER BY id ROWS BETWEEN 1 PRECEDING AND CURRENT ROW) FROM orders';
+  t.deepEqual(db.prepare(sql).pluck().all(), [1, 4, 5, 4]);
+
+  db.aggregate('plain', { step: () => undefined });
+  t.throws(() => db.prepare('SELECT plain() OVER () FROM orders').all(), { message: /may not be used as a window function/ });
+  db.close();
+});
+
+test('errors', (t) => {
+  const db = setup();
+  db.aggregate('fail', {
+    varargs: true,
+    step: () => {
+      throw new Error('Step failed');
+    },
+  });
+
+  t.throws(() => db.prepare('SELECT fail(id) FROM orders').get(), { message: /Step failed/ });
+  t.throws(() => db.aggregate('invalid', {} as any));
+  db.close();
+});
@@ -0,0 +1,84 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+  return db;
+}
+
+test('execAsync', async (t) => {
+  const db = setup();
+  const promise = db.execAsync("INSERT INTO users (name) VALUES ('Amniel'), ('Rust')");
+
+  t.true(promise instanceof Promise);
+  t.is(await promise, undefined);
+  t.is(db.prepare('SELECT count(*) FROM users').pluck().get(), 2);
+  await t.throwsAsync(db.execAsync('SELECT * FROM missing'), { message: /no such table: missing/ });
+  db.close();
+});
+
+test('runAsync', async (t) => {
+  const db = setup();
+  const insert = db.prepare('INSERT INTO users (name) VALUES (?)');
+
+  t.deepEqual(await insert.runAsync(['Amniel']), { changes: 1, lastInsertRowid: 1 });
+  t.deepEqual(await insert.safeIntegers().runAsync(['Rust']), { changes: 1, lastInsertRowid: 2n });
+  await t.throwsAsync(insert.runAsync([]), { message: 'Expected 1 parameters, got 0' });
+  db.close();
+});
+
+test('allAsync', async (t) => {
+  const db = setup();
+  db.exec("INSERT INTO users (name) VALUES ('Amniel'), ('Rust')");
+  const select = db.prepare('SELECT * FROM users WHERE id >= :id');
+
+  const [first, second] = await Promise.all([select.allAsync({ id: 1 }), select.pluck().allAsync({ id: 2 })]);
+  t.deepEqual(first, [
+    { id: 1, name: 'Amniel' },
+    { id: 2, name: 'Rust' },
+  ]);
+  t.deepEqual(second, [2]);
+  t.throws(() => db.prepare("INSERT INTO users (name) VALUES ('Node')").allAsync(), {
+    message: 'This statement does not return data. Use run() instead',
+  });
+  db.close();
+});
+
+test('queue', async (t) => {
+  const db = setup();
+  const insert = db.prepare('INSERT INTO users (name) VALUES (?)');
+  const select = db.prepare('SELECT name FROM users ORDER BY id').pluck();
+  const inserts = ['Amniel', 'Rust', 'Node'].map((name) => insert.runAsync([name]));
+  const names = select.allAsync();
+
+  t.throws(() => select.get(), { message: 'This database connection is busy executing a query' });
+  t.throws(() => select.iterate(), { message: 'This database connection is busy executing a query' });
+  t.deepEqual(
+    (await Promise.all(inserts)).map(({ lastInsertRowid }) => lastInsertRowid),
+    [1, 2, 3],
+  );
+  t.deepEqual(await names, ['Amniel', 'Rust', 'Node']);
+  t.is(select.get(), 'Amniel');
+  db.close();
+});
+
+test('close', async (t) => {
+  const db = setup();
+  const promise = db.execAsync("INSERT INTO users (name) VALUES ('Amniel')");
+
+  t.throws(() => db.close(), { message: 'This database connection is busy executing a query' });
+  await promise;
+  db.close();
+  t.throws(() => db.execAsync('SELECT 1'), { message: 'The database connection is not open' });
+});
+
+test('functions', async (t) => {
+  const db = setup();
+  db.function('double', (n: number) => n * 2);
+
+  t.is(db.prepare('SELECT double(2)').pluck().get(), 4);
+  await t.throwsAsync(db.prepare('SELECT double(2)').allAsync(), { message: /cannot be called from async queries/ });
+  db.close();
+});
@@ -0,0 +1,62 @@
+import test from 'ava';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { BackupProgress, Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  const db = new Database(':memory:');
+  db.exec(`
+    CREATE TABLE logs (id INTEGER PRIMARY KEY, message TEXT);
+    WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 2000)
+    INSERT INTO logs (message) SELECT printf('message %d %s', n, hex(randomblob(64))) FROM seq;
+  `);
+  return { db, dir, cleanup: () => fs.rmSync(dir, { recursive: true, force: true }) };
+}
+
+test('backup', async (t) => {
+  const { db, dir, cleanup } = setup();
+  const file = path.join(dir, 'backup.db');
+
+  const promise = db.backup(file);
+  t.true(promise instanceof Promise);
+  t.throws(() => db.close(), { message: 'This database connection is busy executing a query' });
+  await promise;
+
+  const copy = new Database(file);
+  t.is(copy.prepare('SELECT count(*) FROM logs').pluck().get(), 2000);
+  copy.close();
+  db.close();
+  cleanup();
+});
+
+test('progress', async (t) => {
+  const { db, dir, cleanup } = setup();
+  const calls: BackupProgress[] = [];
+
+  await db.backup(path.join(dir, 'backup.db'), { pages: 10, progress: (progress) => calls.push(progress) });
+
+  t.true(calls.length > 1);
+  t.true(calls.every(({ totalPages }) => totalPages === calls[0].totalPages));
+  t.true(calls[0].remainingPages > calls[calls.length - 1].remainingPages);
+  db.close();
+  cleanup();
+});
+
+test('errors', async (t) => {
+  const { db, dir, cleanup } = setup();
+  await t.throwsAsync(db.backup(path.join(dir, 'missing', 'backup.db')), { message: /unable to open database/ });
+
+  const error = new Error('Out of space');
+  const progress = () => {
+    throw error;
+  };
+  t.is(await t.throwsAsync(db.backup(path.join(dir, 'backup.db'), { pages: 10, progress })), error);
+  t.is(db.prepare('SELECT count(*) FROM logs').pluck().get(), 2000);
+
+  db.close();
+  t.throws(() => db.backup(path.join(dir, 'backup.db')), { message: 'The database connection is not open' });
+  cleanup();
+});
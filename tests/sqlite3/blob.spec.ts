@@ -0,0 +1,52 @@
+import test from 'ava';
+import { Readable } from 'node:stream';
+import { pipeline } from 'node:stream/promises';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE files (id INTEGER PRIMARY KEY, data BLOB)');
+  db.prepare('INSERT INTO files (data) VALUES (zeroblob(?))').run([10]);
+  return db;
+}
+
+test('read and write', (t) => {
+  const db = setup();
+  const blob = db.openBlob('files', 'data', 1);
+
+  t.is(blob.size, 10);
+  blob.write(2, Buffer.from('hello'));
+  t.deepEqual(blob.read(2, 5), Buffer.from('hello'));
+  t.deepEqual(blob.read(8, 10), Buffer.alloc(2));
+  t.deepEqual(db.prepare('SELECT data FROM files').pluck().get(), Buffer.from('\0\0hello\0\0\0'));
+  t.deepEqual(blob.read(0, 0xffffffff), Buffer.from('\0\0hello\0\0\0'));
+  t.deepEqual(blob.read(10, 1), Buffer.alloc(0));
+  t.throws(() => blob.read(11, 1), { code: 'InvalidArg', message: 'Cannot access 0 bytes at offset 11 of a BLOB of 10 bytes' });
+  t.throws(() => blob.write(8, Buffer.from('hello')), { code: 'InvalidArg', message: 'Cannot access 5 bytes at offset 8 of a BLOB of 10 bytes' });
+  t.throws(() => blob.write(0xffffffff, Buffer.from('x')), { code: 'InvalidArg' });
+
+  const readonly = db.openBlob('files', 'data', 1n, { readonly: true });
+  t.true(readonly.readonly);
+  t.throws(() => readonly.write(0, Buffer.from('x')));
+  t.throws(() => db.openBlob('files', 'data', 2), { message: /no such rowid: 2/ });
+  t.throws(() => db.openBlob('files', 'data', 2n ** 64n + 1n), { message: 'BigInt is out of range for a 64-bit integer' });
+  db.close();
+});
+
+test('streams', async (t) => {
+  const db = setup();
+  const content = Buffer.from('0123456789');
+  await pipeline(Readable.from([content.subarray(0, 4), content.subarray(4)]), db.openBlob('files', 'data', 1).createWriteStream());
+
+  const chunks: Buffer[] = [];
+  for await (const chunk of db.openBlob('files', 'data', 1).createReadStream({ highWaterMark: 3 })) chunks.push(chunk);
+  t.deepEqual(chunks.map(String), ['012', '345', '678', '9']);
+
+  const partial: Buffer[] = [];
+  for await (const chunk of db.openBlob('files', 'data', 1).createReadStream({ start: 2, end: 6 })) partial.push(chunk);
+  t.is(Buffer.concat(partial).toString(), '2345');
+
+  await t.throwsAsync(pipeline(Readable.from([Buffer.alloc(11)]), db.openBlob('files', 'data', 1).createWriteStream()));
+  db.close();
+});
@@ -0,0 +1,125 @@
+import test from 'ava';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT, active INTEGER)');
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  return { db, dir };
+}
+
+test('importCsv', (t) => {
+  const { db, dir } = setup();
+  const file = path.join(dir, 'users.csv');
+  fs.writeFileSync(file, '﻿Name,E-mail,Active,Notes\r\nAmniel,amniel@example.com,yes,\r\n"Rust, the crab","",false,"multi\nline ""notes"""\r\n\r\n');
+
+  const count = db.importCsv('users', file, {
+    columns: { Name: 'name', 'E-mail': 'email', Active: 'active' },
+    types: { active: 'boolean' },
+    nullValue: '',
+  });
+  t.is(count, 2);
+  t.deepEqual(db.prepare('SELECT name, email, active FROM users ORDER BY id').all(), [
+    { name: 'Amniel', email: 'amniel@example.com', active: 1 },
+    { name: 'Rust, the crab', email: null, active: 0 },
+  ]);
+
+  fs.writeFileSync(file, '3;Node;node@example.com\n4;Deno;deno@example.com\n');
+  t.is(db.importCsv('users', file, { header: false, delimiter: ';', columns: ['id', 'name', 'email'] }), 2);
+  t.is(db.prepare('SELECT name FROM users WHERE id = 4').pluck().get(), 'Deno');
+
+  fs.writeFileSync(file, '');
+  t.is(db.importCsv('users', file), 0);
+  fs.writeFileSync(file, 'Name,E-mail\n');
+  t.is(db.importCsv('users', file, { columns: [] }), 0);
+
+  t.throws(() => db.importCsv('users', file, { header: false }), { message: 'Files without a header line need `columns` to name their fields' });
+  t.throws(() => db.importCsv('users', path.join(dir, 'missing.csv')), { message: /^Cannot read ".*missing\.csv": / });
+  db.close();
+  fs.rmSync(dir, { recursive: true });
+});
+
+test('import errors roll back', (t) => {
+  const { db, dir } = setup();
+  const file = path.join(dir, 'users.csv');
+
+  fs.writeFileSync(file, 'name,active\nAmniel,yes\nRust,maybe\n');
+  t.throws(() => db.importCsv('users', file, { types: { active: 'boolean' } }), {
+    message: 'Cannot convert "maybe" to boolean for column "active" on line 3',
+  });
+  fs.writeFileSync(file, 'name,active\nAmniel,1\nRust\n');
+  t.throws(() => db.importCsv('users', file), { message: 'Expected 2 fields on line 3, got 1' });
+  fs.writeFileSync(file, 'name,active\nAmniel,1\n,1\n');
+  t.throws(() => db.importCsv('users', file, { nullValue: '' }), { code: 'SQLITE_CONSTRAINT_NOTNULL' });
+  t.throws(() => db.importCsv('users', file, { types: { nickname: 'text' } }), { message: 'Column "nickname" of `types` is not imported' });
+  t.throws(() => db.importCsv('users', file, { delimiter: '||' }), { message: 'Invalid delimiter "||", expected a single character' });
+  t.is(db.prepare('SELECT count(*) FROM users').pluck().get(), 0);
+
+  db.exec('BEGIN');
+  db.prepare("INSERT INTO users (name) VALUES ('Node')").run();
+  t.throws(() => db.importCsv('users', file, { nullValue: '' }));
+  t.true(db.inTransaction);
+  db.exec('COMMIT');
+  t.is(db.prepare('SELECT count(*) FROM users').pluck().get(), 1);
+
+  db.close();
+  fs.rmSync(dir, { recursive: true });
+});
+
+test('importJson', (t) => {
+  const { db, dir } = setup();
+
+  const rows = [
+    { name: 'Amniel', email: 'amniel@example.com', active: true },
+    { name: 'Rust', active: '0', extra: 'ignored' },
+  ];
+  t.is(db.importJson('users', rows, { columns: ['name', 'email', 'active'], types: { active: 'boolean' } }), 2);
+  t.deepEqual(db.prepare('SELECT name, email, active FROM users ORDER BY id').all(), [
+    { name: 'Amniel', email: 'amniel@example.com', active: 1 },
+    { name: 'Rust', email: null, active: 0 },
+  ]);
+
+  t.is(db.importJson('users', [{ fullName: 'Node', id: '10' }], { columns: { fullName: 'name', id: 'id' }, types: { id: 'integer' } }), 1);
+  t.is(db.prepare('SELECT name FROM users WHERE id = 10').pluck().get(), 'Node');
+  t.is(db.importJson('users', []), 0);
+  t.is(db.importJson('users', [{}, { name: 'Deno' }]), 0);
+  t.is(db.importJson('users', [{ name: 'Deno' }], { columns: [] }), 0);
+
+  const many = Array.from({ length: 10000 }, (_, i) => ({ name: `user ${i}`, active: i % 2 }));
+  t.is(db.importJson('users', many), 10000);
+
+  t.throws(() => db.importJson('users', [{ name: 'Deno', email: { work: 'deno@example.com' } }]), {
+    message: 'Row 0, field "email": SQLite cannot bind values of type "Object"',
+  });
+  t.throws(() => db.importJson('users', [{ name: 'Deno', id: 'eleven' }], { types: { id: 'integer' } }), {
+    message: 'Row 0, field "id": Cannot convert "eleven" to integer for column "id"',
+  });
+  t.is(db.prepare('SELECT count(*) FROM users').pluck().get(), 10003);
+  db.close();
+  fs.rmSync(dir, { recursive: true });
+});
+
+test('exportCsv', (t) => {
+  const { db, dir } = setup();
+  const file = path.join(dir, 'export.csv');
+  db.importJson('users', [
+    { name: 'Amniel', email: 'amniel@example.com', active: 1 },
+    { name: 'Rust, "the crab"', email: null, active: 0 },
+  ]);
+
+  t.is(db.exportCsv('SELECT name, email, active, x\'CAFE\' AS tag FROM users ORDER BY id', file), 2);
+  t.is(fs.readFileSync(file, 'utf8'), 'name,email,active,tag\nAmniel,amniel@example.com,1,CAFE\n"Rust, ""the crab""",,0,CAFE\n');
+
+  db.exportCsv('SELECT name, email FROM users ORDER BY id', file, { delimiter: '\t', header: false, nullValue: 'NULL' });
+  t.is(fs.readFileSync(file, 'utf8'), 'Amniel\tamniel@example.com\n"Rust, ""the crab"""\tNULL\n');
+
+  db.exec('DELETE FROM users');
+  t.is(db.importCsv('users', file, { header: false, delimiter: '\t', columns: ['name', 'email'], nullValue: 'NULL' }), 2);
+  t.is(db.prepare('SELECT email FROM users WHERE name LIKE \'Rust%\'').pluck().get(), null);
+  db.close();
+  fs.rmSync(dir, { recursive: true });
+});
@@ -0,0 +1,81 @@
+import test from 'ava';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function open() {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  const file = path.join(dir, 'busy.db');
+  const writer = new Database(file);
+  writer.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+  writer.exec('BEGIN IMMEDIATE');
+  return { writer, other: new Database(file), dir };
+}
+
+test('busyTimeout', (t) => {
+  const { writer, other, dir } = open();
+
+  other.busyTimeout(50);
+  const start = Date.now();
+  t.throws(() => other.exec("INSERT INTO users (name) VALUES ('Amniel')"), { code: 'SQLITE_BUSY' });
+  t.true(Date.now() - start >= 40);
+
+  writer.close();
+  other.close();
+  fs.rmSync(dir, { recursive: true });
+});
+
+test('busyHandler', (t) => {
+  const { writer, other, dir } = open();
+
+  const calls: number[] = [];
+  other.busyHandler(
+    (retries) => {
+      calls.push(retries);
+      return retries < 3;
+    },
+    { delay: 1 },
+  );
+  t.throws(() => other.exec("INSERT INTO users (name) VALUES ('Amniel')"), { code: 'SQLITE_BUSY' });
+  t.deepEqual(calls, [0, 1, 2, 3]);
+
+  other.busyHandler((retries) => {
+    if (retries === 1) writer.exec('COMMIT');
+    return true;
+  });
+  other.exec("INSERT INTO users (name) VALUES ('Amniel')");
+  t.is(other.prepare('SELECT count(*) FROM users').pluck().get(), 1);
+
+  writer.exec('BEGIN IMMEDIATE');
+  const thrown = new Error('Stop waiting');
+  other.busyHandler(() => {
+    throw thrown;
+  });
+  t.is(t.throws(() => other.exec("INSERT INTO users (name) VALUES ('Rust')")), thrown);
+  writer.exec('ROLLBACK');
+
+  writer.close();
+  other.close();
+  fs.rmSync(dir, { recursive: true });
+});
+
+test('backoff', async (t) => {
+  const { writer, other, dir } = open();
+
+  other.busyHandler(null, { retries: 3, delay: 10, maxDelay: 20 });
+  const start = Date.now();
+  t.throws(() => other.exec("INSERT INTO users (name) VALUES ('Amniel')"), { code: 'SQLITE_BUSY' });
+  // Waits 10, 20 and 20 ms.
+  t.true(Date.now() - start >= 45);
+
+  const calls: number[] = [];
+  other.busyHandler((retries) => calls.push(retries) > 0, { retries: 2, delay: 1 });
+  await t.throwsAsync(other.execAsync("INSERT INTO users (name) VALUES ('Amniel')"), { code: 'SQLITE_BUSY' });
+  t.deepEqual(calls, []);
+
+  writer.close();
+  other.close();
+  fs.rmSync(dir, { recursive: true });
+});
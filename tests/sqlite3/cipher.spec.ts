@@ -0,0 +1,174 @@
+import test from 'ava';
+import { spawn } from 'node:child_process';
+import crypto from 'node:crypto';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function tempFile() {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  return { file: path.join(dir, 'cipher.db'), cleanup: () => fs.rmSync(dir, { recursive: true, force: true }) };
+}
+
+function fill(db: Database) {
+  db.exec('CREATE TABLE secrets (id INTEGER PRIMARY KEY, value TEXT)');
+  const insert = db.prepare('INSERT INTO secrets (value) VALUES (?)');
+  db.transaction(() => {
+    for (let i = 0; i < 500; i++) insert.run([`top secret ${i} `.repeat(10)]);
+  })();
+}
+
+test('encrypted at rest', (t) => {
+  const { file, cleanup } = tempFile();
+  const key = crypto.randomBytes(32);
+
+  const db = new Database(file, { key });
+  fill(db);
+  db.exec('DELETE FROM secrets WHERE id > 400');
+  db.close();
+
+  const content = fs.readFileSync(file);
+  t.false(content.includes('SQLite format 3'));
+  t.false(content.includes('top secret'));
+
+  const reopened = new Database(file, { key });
+  t.is(reopened.prepare('SELECT count(*) FROM secrets').pluck().get(), 400);
+  reopened.close();
+
+  t.throws(() => new Database(file, { key: crypto.randomBytes(32) }), { message: /file is not a database/ });
+  const plain = new Database(file);
+  t.throws(() => plain.prepare('SELECT * FROM secrets'), { message: /file is not a database/ });
+  plain.close();
+  t.throws(() => new Database(file, { key: Buffer.alloc(16) }), { message: 'Invalid key length. Must be 32 bytes.' });
+  cleanup();
+});
+
+test('tampering', (t) => {
+  const { file, cleanup } = tempFile();
+  const key = crypto.randomBytes(32);
+
+  const db = new Database(file, { key });
+  fill(db);
+  db.close();
+
+  const content = fs.readFileSync(file);
+  content[content.length - 100] ^= 1;
+  fs.writeFileSync(file, content);
+
+  const reopened = new Database(file, { key });
+  t.throws(() => reopened.prepare('SELECT * FROM secrets').all(), { message: /malformed/ });
+  reopened.close();
+  cleanup();
+});
+
+test('wal', (t) => {
+  const { file, cleanup } = tempFile();
+  const key = crypto.randomBytes(32);
+
+  const db = new Database(file, { key, journalMode: 'wal' });
+  fill(db);
+  t.false(fs.readFileSync(`${file}-wal`).includes('top secret'));
+  t.is(db.prepare('SELECT count(*) FROM secrets').pluck().get(), 500);
+  db.close();
+
+  const reopened = new Database(file, { key });
+  t.is(reopened.pragma('journal_mode', undefined, { simple: true }), 'wal');
+  t.is(reopened.prepare('SELECT count(*) FROM secrets').pluck().get(), 500);
+  reopened.close();
+  cleanup();
+});
+
+for (const journalMode of ['delete', 'wal'] as const) {
+  test(`rekey (${journalMode})`, (t) => {
+    const { file, cleanup } = tempFile();
+    const oldKey = crypto.randomBytes(32);
+    const newKey = crypto.randomBytes(32);
+
+    const db = new Database(file, { key: oldKey, journalMode, cacheSize: 10 });
+    fill(db);
+    db.rekey(newKey);
+    t.is(db.prepare('SELECT count(*) FROM secrets').pluck().get(), 500);
+    t.is(db.pragma('journal_mode', undefined, { simple: true }), journalMode);
+    t.is(db.pragma('integrity_check', undefined, { simple: true }), 'ok');
+    db.exec("INSERT INTO secrets (value) VALUES ('after rekey')");
+    db.close();
+
+    t.throws(() => new Database(file, { key: oldKey }), { message: /file is not a database/ });
+    const reopened = new Database(file, { key: newKey });
+    t.is(reopened.prepare('SELECT count(*) FROM secrets').pluck().get(), 501);
+    t.is(reopened.pragma('integrity_check', undefined, { simple: true }), 'ok');
+    reopened.close();
+    cleanup();
+  });
+}
+
+test('rekey interrupted', async (t) => {
+  const oldKey = crypto.randomBytes(32);
+  const newKey = crypto.randomBytes(32);
+  const lib = path.join(__dirname, '../../packages/sqlite3/lib');
+
+  // Kills a process changing the key once it overwrote the first page, retrying when it committed first.
+  for (let attempt = 1; ; attempt++) {
+    const { file, cleanup } = tempFile();
+    const db = new Database(file, { key: oldKey });
+    fill(db);
+    db.exec(`
+      WITH RECURSIVE n (i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2000)
+      INSERT INTO secrets (value) SELECT hex(randomblob(2000)) FROM n
+    `);
+    db.close();
+
+    const header = () => {
+      const fd = fs.openSync(file, 'r');
+      const bytes = Buffer.alloc(32);
+      fs.readSync(fd, bytes, 0, bytes.length, 0);
+      fs.closeSync(fd);
+      return bytes;
+    };
+    const before = header();
+    const child = spawn(process.execPath, [
+      '-e',
+      `const { Database } = require(${JSON.stringify(lib)});
+      new Database(${JSON.stringify(file)}, { key: Buffer.from('${oldKey.toString('hex')}', 'hex') }).rekey(Buffer.from('${newKey.toString('hex')}', 'hex'));`,
+    ]);
+    const exited = new Promise((resolve) => child.on('exit', resolve));
+    const deadline = Date.now() + 30_000;
+    while (header().equals(before) && Date.now() < deadline);
+    child.kill('SIGKILL');
+    await exited;
+
+    if (!fs.existsSync(`${file}-journal`) && attempt < 10) {
+      cleanup();
+      continue;
+    }
+    t.true(fs.existsSync(`${file}-journal`));
+    t.false(header().equals(before));
+
+    const reopened = new Database(file, { key: oldKey });
+    t.is(reopened.prepare('SELECT count(*) FROM secrets').pluck().get(), 2500);
+    t.is(reopened.pragma('integrity_check', undefined, { simple: true }), 'ok');
+    reopened.close();
+    t.false(fs.existsSync(`${file}-journal`));
+    t.throws(() => new Database(file, { key: newKey }), { message: /file is not a database/ });
+    cleanup();
+    break;
+  }
+});
+
+test('rekey errors', (t) => {
+  const { file, cleanup } = tempFile();
+  const key = crypto.randomBytes(32);
+
+  const plain = new Database(':memory:');
+  t.throws(() => plain.rekey(key), { message: 'The database is not encrypted, open it with a key' });
+  plain.close();
+
+  const db = new Database(file, { key });
+  db.exec('BEGIN');
+  t.throws(() => db.rekey(key), { message: 'Cannot change the key inside a transaction' });
+  db.exec('ROLLBACK');
+  db.close();
+  cleanup();
+});
@@ -0,0 +1,53 @@
+import test from 'ava';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+test('open:memory', (t) => {
+  const db = new Database(':memory:');
+  t.is(db.name, ':memory:');
+  t.is(db.filename, '');
+  t.true(db.isOpen);
+  t.false(db.inTransaction);
+  db.close();
+});
+
+test('open:file', (t) => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  const file = path.join(dir, 'test.db');
+
+  const db = new Database(file);
+  t.is(db.name, file);
+  t.is(db.filename, fs.realpathSync(dir) + path.sep + 'test.db');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+  db.close();
+
+  t.true(fs.existsSync(file));
+  fs.rmSync(dir, { recursive: true, force: true });
+});
+
+test('exec', (t) => {
+  const db = new Database(':memory:');
+  db.exec(`
+    CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+    INSERT INTO users (name) VALUES ('Amniel');
+  `);
+
+  db.exec('BEGIN');
+  t.true(db.inTransaction);
+  db.exec('COMMIT');
+  t.false(db.inTransaction);
+
+  t.throws(() => db.exec('SELECT * FROM missing'), { message: /no such table/ });
+  db.close();
+});
+
+test('close', (t) => {
+  const db = new Database(':memory:');
+  db.close();
+  t.false(db.isOpen);
+  t.notThrows(() => db.close());
+  t.throws(() => db.exec('SELECT 1'), { message: 'The database connection is not open' });
+});
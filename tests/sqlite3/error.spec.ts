@@ -0,0 +1,47 @@
+import test from 'ava';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+test('constraint errors', (t) => {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)');
+  const insert = db.prepare('INSERT INTO users (name) VALUES (?)');
+  insert.run(['Amniel']);
+
+  t.throws(() => insert.run(['Amniel']), {
+    code: 'SQLITE_CONSTRAINT_UNIQUE',
+    message: 'UNIQUE constraint failed: users.name',
+  });
+  t.like(t.throws(() => insert.run([null])), { code: 'SQLITE_CONSTRAINT_NOTNULL', errno: 19, extendedCode: 1299 });
+  db.close();
+});
+
+test('syntax errors', (t) => {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+
+  t.like(t.throws(() => db.prepare('SELECT nickname FROM users')), { code: 'SQLITE_ERROR', errno: 1, extendedCode: 1, offset: 7 });
+  t.like(t.throws(() => db.exec('DROP TABLE missing')), { code: 'SQLITE_ERROR', offset: undefined });
+
+  db.close();
+});
+
+test('busy errors', async (t) => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  const file = path.join(dir, 'busy.db');
+  const writer = new Database(file);
+  const other = new Database(file, { timeout: 0 });
+  writer.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+  writer.exec('BEGIN IMMEDIATE');
+
+  t.like(t.throws(() => other.exec("INSERT INTO users (name) VALUES ('Amniel')")), { code: 'SQLITE_BUSY', errno: 5 });
+  await t.throwsAsync(other.execAsync("INSERT INTO users (name) VALUES ('Amniel')"), { code: 'SQLITE_BUSY' });
+
+  writer.exec('COMMIT');
+  writer.close();
+  other.close();
+  fs.rmSync(dir, { recursive: true });
+});
@@ -0,0 +1,48 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+test('bundled extensions', (t) => {
+  const db = new Database(':memory:');
+
+  db.exec("CREATE VIRTUAL TABLE messages USING fts5(key, text); INSERT INTO messages VALUES ('hello', 'Hello world'), ('bye', 'Goodbye');");
+  t.deepEqual(db.prepare("SELECT key FROM messages WHERE messages MATCH 'world'").pluck().all(), ['hello']);
+
+  t.is(db.prepare("SELECT json_extract('{\"a\": [1, 2]}', '$.a[1]')").pluck().get(), 2);
+
+  db.exec('CREATE VIRTUAL TABLE places USING rtree(id, minX, maxX, minY, maxY); INSERT INTO places VALUES (1, 0, 10, 0, 10), (2, 20, 30, 20, 30);');
+  t.deepEqual(db.prepare('SELECT id FROM places WHERE minX <= 5 AND maxX >= 5').pluck().all(), [1]);
+  db.close();
+});
+
+test('math functions', (t) => {
+  const db = new Database(':memory:');
+  const math = (sql: string) => db.prepare(`SELECT ${sql}`).pluck().get();
+
+  t.is(math('sqrt(16)'), 4);
+  t.is(math('pow(2, 10)'), 1024);
+  t.is(math('log(100)'), 2);
+  t.is(math('log(2, 8)'), 3);
+  t.is(math('round(pi(), 5)'), 3.14159);
+  t.is(math('floor(-1.5)'), -2);
+  t.is(math('ceil(7)'), 7);
+  t.is(math('mod(7, 3)'), 1);
+  t.is(math("sqrt('9')"), 3);
+  t.is(math('sqrt(-1)'), null);
+  t.is(math('ln(0)'), null);
+  t.is(math('sqrt(NULL)'), null);
+  db.close();
+});
+
+test('loadExtension', (t) => {
+  const db = new Database(':memory:');
+  t.throws(() => db.loadExtension('./missing'), {
+    message: 'Loading extensions is disabled, open the database with `allowExtensions: true`',
+  });
+  db.close();
+
+  const allowed = new Database(':memory:', { allowExtensions: true });
+  t.throws(() => allowed.loadExtension('./missing'), { message: /missing/ });
+  t.throws(() => allowed.exec("SELECT load_extension('./missing')"), { message: 'not authorized' });
+  allowed.close();
+});
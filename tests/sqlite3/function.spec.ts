@@ -0,0 +1,72 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  return new Database(':memory:');
+}
+
+test('scalar', (t) => {
+  const db = setup();
+  t.is(
+    db.function('double', (n: number) => n * 2),
+    db,
+  );
+
+  t.deepEqual(db.prepare('SELECT double(21) AS answer').get(), { answer: 42 });
+  t.throws(() => db.prepare('SELECT double(1, 2)'), { message: /wrong number of arguments/ });
+  db.close();
+});
+
+test('values', (t) => {
+  const db = setup();
+  db.function('echo', (value: unknown) => value);
+  const echo = db.prepare('SELECT echo(?) AS value').pluck();
+
+  t.is(echo.get([null]), null);
+  t.is(echo.get([1.5]), 1.5);
+  t.is(echo.get(['text']), 'text');
+  t.deepEqual(echo.get([Buffer.from([1, 2])]), Buffer.from([1, 2]));
+  t.is(echo.get([true]), 1);
+
+  db.function('empty', () => undefined);
+  t.is(db.prepare('SELECT empty()').pluck().get(), null);
+  db.close();
+});
+
+test('safe integers', (t) => {
+  const db = setup();
+  db.safeIntegers().function('type', (value: unknown) => typeof value);
+  t.is(db.prepare('SELECT type(1)').pluck().get(), 'bigint');
+  db.close();
+});
+
+test('options', (t) => {
+  const db = setup();
+  db.function('dashed', { varargs: true, deterministic: true }, (...args: unknown[]) => args.join('-'));
+  t.is(db.prepare("SELECT dashed('a', 'b', 'c')").pluck().get(), 'a-b-c');
+  t.is(db.prepare('SELECT dashed()').pluck().get(), '');
+
+  db.function('secret', { directOnly: true }, () => 'secret');
+  t.is(db.prepare('SELECT secret()').pluck().get(), 'secret');
+  db.exec('CREATE VIEW secrets AS SELECT secret() AS value');
+  t.throws(() => db.prepare('SELECT * FROM secrets').get(), { message: /unsafe use of secret/ });
+  db.close();
+});
+
+test('errors', (t) => {
+  const db = setup();
+  const error = new Error('Function failed');
+  db.function('fail', () => {
+    throw error;
+  });
+  db.function('symbol', () => Symbol('value'));
+
+  t.is(t.throws(() => db.prepare('SELECT fail()').get()), error);
+  t.is(t.throws(() => db.prepare('SELECT fail()').all()), error);
+  t.is(t.throws(() => [...db.prepare('SELECT fail()').iterate()]), error);
+  t.is(db.prepare('SELECT 1').pluck().get(), 1);
+  t.throws(() => db.prepare('SELECT symbol()').get(), { message: /cannot bind values of type/ });
+  t.throws(() => db.function('missing', {} as any), { message: 'Expected a function as the last argument' });
+  db.close();
+});
@@ -0,0 +1,53 @@
+import test from 'ava';
+
+import { Database, UpdateEvent } from '../../packages/sqlite3/lib';
+
+const tick = () => new Promise((resolve) => setImmediate(resolve));
+
+test('update', async (t) => {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+
+  const events: UpdateEvent[] = [];
+  db.on('update', (event) => events.push(event));
+  db.exec("INSERT INTO users (name) VALUES ('Amniel'), ('Rust')");
+  db.exec("UPDATE users SET name = 'Node' WHERE id = 2");
+  await db.execAsync('DELETE FROM users WHERE id = 1');
+  await tick();
+
+  t.deepEqual(events, [
+    { op: 'insert', database: 'main', table: 'users', rowid: 1 },
+    { op: 'insert', database: 'main', table: 'users', rowid: 2 },
+    { op: 'update', database: 'main', table: 'users', rowid: 2 },
+    { op: 'delete', database: 'main', table: 'users', rowid: 1 },
+  ]);
+  db.close();
+});
+
+test('commit and rollback', async (t) => {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE items (id INTEGER PRIMARY KEY)');
+
+  const events: string[] = [];
+  const onCommit = () => events.push('commit');
+  db.on('commit', onCommit).on('rollback', () => events.push('rollback'));
+
+  db.exec('INSERT INTO items DEFAULT VALUES');
+  t.throws(() => db.transaction(() => {
+    db.exec('INSERT INTO items DEFAULT VALUES');
+    throw new Error('abort');
+  })());
+  await tick();
+  t.deepEqual(events, ['commit', 'rollback']);
+
+  db.off('commit', onCommit).off('rollback');
+  db.exec('INSERT INTO items DEFAULT VALUES');
+  await tick();
+  t.deepEqual(events, ['commit', 'rollback']);
+  t.is(db.prepare('SELECT count(*) FROM items').pluck().get(), 2);
+
+  t.throws(() => db.on('change' as 'update', () => {}), {
+    message: 'Unknown event "change", expected update, commit or rollback',
+  });
+  db.close();
+});
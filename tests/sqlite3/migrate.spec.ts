@@ -0,0 +1,100 @@
+import test from 'ava';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { Database, Migration } from '../../packages/sqlite3/lib';
+
+const migrations: Migration[] = [
+  {
+    version: 1,
+    name: 'users',
+    up: 'CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);',
+    down: 'DROP TABLE users;',
+  },
+  {
+    version: 2,
+    name: 'posts',
+    up: 'CREATE TABLE posts (id INTEGER PRIMARY KEY, title TEXT);',
+    down: 'DROP TABLE posts;',
+  },
+];
+
+function tables(db: Database) {
+  return db.prepare("SELECT name FROM sqlite_schema WHERE type = 'table' ORDER BY name").pluck().all();
+}
+
+test('migrations', (t) => {
+  const db = new Database(':memory:');
+
+  t.deepEqual(db.migrate({ migrations }), { version: 2, applied: [1, 2], reverted: [] });
+  t.deepEqual(tables(db), ['migrations', 'posts', 'users']);
+  t.deepEqual(db.prepare('SELECT version, name FROM migrations').raw().all(), [
+    [1, 'users'],
+    [2, 'posts'],
+  ]);
+  t.deepEqual(db.migrate({ migrations }), { version: 2, applied: [], reverted: [] });
+  db.close();
+});
+
+test('rollback', (t) => {
+  const db = new Database(':memory:');
+  db.migrate({ migrations, table: 'schema_versions' });
+
+  t.deepEqual(db.migrate({ migrations, table: 'schema_versions', to: 0 }), { version: 0, applied: [], reverted: [2, 1] });
+  t.deepEqual(tables(db), ['schema_versions']);
+  t.deepEqual(db.migrate({ migrations, table: 'schema_versions', to: 1 }), { version: 1, applied: [1], reverted: [] });
+
+  const withoutDown = [migrations[0], { version: 2, name: 'posts', up: migrations[1].up }];
+  db.migrate({ migrations: withoutDown, table: 'schema_versions' });
+  t.throws(() => db.migrate({ migrations: withoutDown, table: 'schema_versions', to: 1 }), {
+    message: 'Migration 2 (posts) has no down script',
+  });
+  db.close();
+});
+
+test('checksum', (t) => {
+  const db = new Database(':memory:');
+  db.migrate({ migrations });
+
+  const changed = [migrations[0], { ...migrations[1], up: 'CREATE TABLE posts (id INTEGER PRIMARY KEY);' }];
+  t.throws(() => db.migrate({ migrations: changed }), { message: 'Migration 2 (posts) was changed after it was applied' });
+  t.throws(() => db.migrate({ migrations: [migrations[1]] }), { message: 'Applied migration 1 is missing' });
+  db.close();
+});
+
+test('failure', (t) => {
+  const db = new Database(':memory:');
+  const broken = [...migrations, { version: 3, name: 'broken', up: 'CREATE TABLE tags (id INTEGER); SELECT * FROM missing;' }];
+
+  const error = t.throws(() => db.migrate({ migrations: broken }), { message: /^Migration 3 \(broken\) failed: no such table: missing/ });
+  t.like(error, { code: 'SQLITE_ERROR', errno: 1 });
+  const unique = [...migrations, { version: 3, name: 'unique', up: 'INSERT INTO users (id) VALUES (1), (1);' }];
+  t.like(t.throws(() => db.migrate({ migrations: unique })), { code: 'SQLITE_CONSTRAINT_PRIMARYKEY', errno: 19 });
+  t.deepEqual(tables(db), ['migrations', 'posts', 'users']);
+  t.is(db.prepare('SELECT max(version) FROM migrations').pluck().get(), 2);
+  t.false(db.inTransaction);
+
+  t.throws(() => db.migrate({}), { message: 'Expected either a directory or a list of migrations' });
+  t.throws(() => db.migrate({ migrations: [migrations[0], migrations[0]] }), { message: 'Duplicate migration version 1' });
+  db.close();
+});
+
+test('directory', (t) => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  fs.writeFileSync(path.join(dir, '001-users.sql'), 'CREATE TABLE users (id INTEGER PRIMARY KEY);\n-- down\nDROP TABLE users;\n');
+  fs.writeFileSync(path.join(dir, '002_posts.sql'), 'CREATE TABLE posts (id INTEGER PRIMARY KEY);\n');
+  fs.writeFileSync(path.join(dir, 'README.md'), 'Not a migration');
+
+  const db = new Database(':memory:');
+  t.deepEqual(db.migrate({ directory: dir }), { version: 2, applied: [1, 2], reverted: [] });
+  t.deepEqual(db.prepare('SELECT name FROM migrations').pluck().all(), ['users', 'posts']);
+  t.throws(() => db.migrate({ directory: dir, to: 0 }), { message: 'Migration 2 (posts) has no down script' });
+
+  fs.writeFileSync(path.join(dir, 'latest.sql'), 'SELECT 1;');
+  t.throws(() => db.migrate({ directory: dir }), { message: 'Migration file "latest.sql" must start with its version number' });
+  t.throws(() => db.migrate({ directory: path.join(dir, 'missing') }), { message: /^Cannot read migrations from/ });
+
+  db.close();
+  fs.rmSync(dir, { recursive: true, force: true });
+});
@@ -0,0 +1,67 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec(`
+    CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+    CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users (id), title VARCHAR(64));
+    INSERT INTO users VALUES (1, 'Amniel'), (2, 'Rust');
+    INSERT INTO posts VALUES (1, 1, 'Hello'), (2, 2, 'World');
+  `);
+  return db;
+}
+
+test('raw', (t) => {
+  const db = setup();
+  const stmt = db.prepare('SELECT id, name FROM users ORDER BY id').raw();
+  t.deepEqual(stmt.get(), [1, 'Amniel']);
+  t.deepEqual(stmt.all(), [[1, 'Amniel'], [2, 'Rust']]);
+  t.deepEqual([...stmt.iterate()], [[1, 'Amniel'], [2, 'Rust']]);
+
+  t.deepEqual(stmt.raw(false).get(), { id: 1, name: 'Amniel' });
+  db.close();
+});
+
+test('pluck', (t) => {
+  const db = setup();
+  const stmt = db.prepare('SELECT name, id FROM users ORDER BY id');
+  t.is(stmt.pluck(), stmt);
+  t.is(stmt.get(), 'Amniel');
+  t.deepEqual(stmt.all(), ['Amniel', 'Rust']);
+  t.deepEqual([...stmt.iterate()], ['Amniel', 'Rust']);
+
+  // Enabling a mode replaces the previous one
+  t.deepEqual(stmt.raw().get(), ['Amniel', 1]);
+  db.close();
+});
+
+test('expand', (t) => {
+  const db = setup();
+  const stmt = db
+    .prepare('SELECT users.*, posts.id, posts.title, 1 + 1 AS two FROM users JOIN posts ON posts.user_id = users.id')
+    .expand();
+
+  t.deepEqual(stmt.all(), [
+    { users: { id: 1, name: 'Amniel' }, posts: { id: 1, title: 'Hello' }, $: { two: 2 } },
+    { users: { id: 2, name: 'Rust' }, posts: { id: 2, title: 'World' }, $: { two: 2 } },
+  ]);
+  t.deepEqual(stmt.expand(false).get(), { id: 1, name: 'Amniel', title: 'Hello', two: 2 });
+  db.close();
+});
+
+test('columns', (t) => {
+  const db = setup();
+  const columns = db.prepare('SELECT p.title AS heading, u.id, length(u.name) AS len FROM posts p JOIN users u').columns();
+  t.deepEqual(columns, [
+    { name: 'heading', column: 'title', table: 'posts', database: 'main', type: 'VARCHAR(64)' },
+    { name: 'id', column: 'id', table: 'users', database: 'main', type: 'INTEGER' },
+    { name: 'len' },
+  ]);
+
+  const insert = db.prepare('INSERT INTO users (name) VALUES (?)');
+  t.throws(() => insert.columns(), { message: /does not return data/ });
+  t.throws(() => insert.raw(), { message: /only available for statements that return data/ });
+  db.close();
+});
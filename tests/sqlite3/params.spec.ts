@@ -0,0 +1,59 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+test('positional', (t) => {
+  const db = new Database(':memory:');
+  const stmt = db.prepare('SELECT ? AS a, ? AS b, ? AS c, ? AS d');
+  t.deepEqual(stmt.get([1, 1.5, 'text', null]), { a: 1, b: 1.5, c: 'text', d: null });
+  t.deepEqual(stmt.get([undefined, true, false, 10n]), { a: null, b: 1, c: 0, d: 10 });
+
+  t.throws(() => stmt.get([1, 2]), { code: 'InvalidArg', message: 'Expected 4 parameters, got 2' });
+  db.close();
+});
+
+test('named', (t) => {
+  const db = new Database(':memory:');
+  const stmt = db.prepare('SELECT :id AS id, @name AS name, $score AS score');
+  t.deepEqual(stmt.get({ id: 1, name: 'Amniel', score: 9.5 }), { id: 1, name: 'Amniel', score: 9.5 });
+  t.deepEqual(stmt.get({ id: 2, name: 'Rust', score: 7, unused: true }), { id: 2, name: 'Rust', score: 7 });
+
+  t.throws(() => stmt.get({ id: 1, name: 'Amniel' }), {
+    code: 'InvalidArg',
+    message: 'Missing named parameter "score"',
+  });
+  t.throws(() => db.prepare('SELECT ?').get({ id: 1 }), { code: 'InvalidArg', message: /anonymous parameters/ });
+  db.close();
+});
+
+test('buffer', (t) => {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE files (data BLOB)');
+  db.prepare('INSERT INTO files VALUES (?)').run([Buffer.from('Hello, world!')]);
+
+  const row = db.prepare('SELECT data, typeof(data) AS type FROM files').get();
+  t.true(Buffer.isBuffer(row!.data));
+  t.is((row!.data as Buffer).toString(), 'Hello, world!');
+  t.is(row!.type, 'blob');
+  db.close();
+});
+
+test('date', (t) => {
+  const db = new Database(':memory:');
+  const date = new Date(Date.UTC(2024, 4, 21, 12, 30));
+  const row = db.prepare("SELECT ? AS date, strftime('%Y', ?) AS year").get([date, date]);
+  t.deepEqual(row, { date: '2024-05-21T12:30:00.000Z', year: '2024' });
+
+  t.throws(() => db.prepare('SELECT ?').get([new Date(NaN)]), { code: 'InvalidArg', message: /Invalid Date/ });
+  db.close();
+});
+
+test('unsupported', (t) => {
+  const db = new Database(':memory:');
+  const stmt = db.prepare('SELECT ?');
+  t.throws(() => stmt.get([{}]), { code: 'InvalidArg', message: /cannot bind values of type "Object"/ });
+  t.throws(() => stmt.get([Symbol('id')]), { code: 'InvalidArg', message: /cannot bind values of type "Symbol"/ });
+  t.throws(() => stmt.get([2n ** 64n]), { code: 'InvalidArg', message: /out of range/ });
+  t.throws(() => stmt.get(1 as any), { code: 'InvalidArg', message: /array or an object/ });
+  db.close();
+});
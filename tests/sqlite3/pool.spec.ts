@@ -0,0 +1,80 @@
+import test from 'ava';
+import crypto from 'node:crypto';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { Database, Pool } from '../../packages/sqlite3/lib';
+
+function setup(readers = 2) {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  const file = path.join(dir, 'pool.db');
+  const pool = new Pool(file, { readers });
+  return { pool, file, cleanup: () => fs.rmSync(dir, { recursive: true, force: true }) };
+}
+
+test('open', (t) => {
+  const { pool, file, cleanup } = setup();
+  t.is(pool.filename, fs.realpathSync(file));
+
+  const db = new Database(file);
+  t.is(db.prepare('PRAGMA journal_mode').pluck().get(), 'wal');
+  db.close();
+
+  t.throws(() => new Pool(':memory:'), { message: /needs a database file/ });
+  t.throws(() => new Pool(file, { readers: 0 }), { message: 'A pool needs at least one reader' });
+  pool.close();
+  cleanup();
+});
+
+test('options', async (t) => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  const file = path.join(dir, 'pool.db');
+  const pool = new Pool(file, { readers: 2, timeout: 100, foreignKeys: true, cacheSize: -1024, journalMode: 'WAL', key: crypto.randomBytes(32) });
+  await pool.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+  await pool.run('INSERT INTO users (name) VALUES (?)', ['Amniel']);
+  t.deepEqual(await Promise.all([pool.get('PRAGMA foreign_keys'), pool.get('PRAGMA cache_size'), pool.get('SELECT name FROM users')]), [
+    { foreign_keys: 1 },
+    { cache_size: -1024 },
+    { name: 'Amniel' },
+  ]);
+  pool.close();
+
+  t.throws(() => new Pool(file, { readonly: true }), { message: 'A pool needs a writable database' });
+  t.throws(() => new Pool(file, { journalMode: 'delete' }), { message: 'Invalid journalMode "delete", a pool needs wal' });
+  fs.rmSync(dir, { recursive: true, force: true });
+});
+
+test('queries', async (t) => {
+  const { pool, cleanup } = setup();
+  await pool.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+
+  t.deepEqual(await pool.run('INSERT INTO users (name) VALUES (?)', ['Amniel']), { changes: 1, lastInsertRowid: 1 });
+  await pool.run('INSERT INTO users (name) VALUES (:name)', { name: 'Rust' });
+
+  t.deepEqual(await pool.get('SELECT * FROM users WHERE id = ?', [2]), { id: 2, name: 'Rust' });
+  t.is(await pool.get('SELECT * FROM users WHERE id = ?', [3]), null);
+  t.deepEqual(await pool.all('SELECT name FROM users ORDER BY id'), [{ name: 'Amniel' }, { name: 'Rust' }]);
+  await t.throwsAsync(pool.all("INSERT INTO users (name) VALUES ('Node')"), { message: /readonly database/ });
+  pool.close();
+  cleanup();
+});
+
+test('concurrency', async (t) => {
+  const { pool, cleanup } = setup(3);
+  await pool.exec('CREATE TABLE numbers (n INTEGER)');
+  await Promise.all(Array.from({ length: 10 }, (_, n) => pool.run('INSERT INTO numbers (n) VALUES (?)', [n])));
+
+  const reads = Array.from({ length: 6 }, () => pool.get('SELECT sum(n) AS total FROM numbers'));
+  const stats = pool.stats();
+  t.is(stats.readers, 3);
+  t.is(stats.busy, 3);
+  t.is(stats.idle, 0);
+  t.false(stats.writerBusy);
+  t.throws(() => pool.close(), { message: 'This database connection is busy executing a query' });
+
+  t.deepEqual(await Promise.all(reads), Array(6).fill({ total: 45 }));
+  t.deepEqual(pool.stats(), { readers: 3, busy: 0, idle: 3, writerBusy: false });
+  pool.close();
+  cleanup();
+});
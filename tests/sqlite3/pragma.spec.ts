@@ -0,0 +1,56 @@
+import test from 'ava';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+test('pragma', (t) => {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+
+  t.deepEqual(db.pragma('cache_size', 1000), []);
+  t.is(db.pragma('cache_size', undefined, { simple: true }), 1000);
+  t.deepEqual(db.pragma('main.cache_size'), [{ cache_size: 1000 }]);
+  t.deepEqual(db.pragma('foreign_keys', true), []);
+  t.is(db.pragma('foreign_keys', undefined, { simple: true }), 1);
+  t.deepEqual(
+    (db.pragma('table_info', 'users') as { name: string }[]).map((column) => column.name),
+    ['id', 'name'],
+  );
+  t.is(db.pragma('table_info', "it's missing", { simple: true }), null);
+
+  t.throws(() => db.pragma('cache_size; DROP TABLE users'), { message: 'Invalid pragma name "cache_size; DROP TABLE users"' });
+  t.throws(() => db.pragma('cache_size', Buffer.from('1')), { message: 'Pragma values must be numbers, strings or booleans' });
+  db.close();
+});
+
+test('options', (t) => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'sqlite3-'));
+  const file = path.join(dir, 'options.db');
+
+  t.throws(() => new Database(file, { fileMustExist: true }), { message: /^unable to open database file/ });
+
+  const db = new Database(file, { journalMode: 'wal', synchronous: 'normal', foreignKeys: true, cacheSize: -4000, timeout: 100 });
+  t.false(db.readonly);
+  t.is(db.pragma('journal_mode', undefined, { simple: true }), 'wal');
+  t.is(db.pragma('synchronous', undefined, { simple: true }), 1);
+  t.is(db.pragma('foreign_keys', undefined, { simple: true }), 1);
+  t.is(db.pragma('cache_size', undefined, { simple: true }), -4000);
+  t.is(db.pragma('busy_timeout', undefined, { simple: true }), 100);
+  db.exec('CREATE TABLE items (id INTEGER PRIMARY KEY)');
+  db.close();
+
+  const readonly = new Database(file, { readonly: true });
+  t.true(readonly.readonly);
+  t.throws(() => readonly.exec('INSERT INTO items DEFAULT VALUES'), { message: 'attempt to write a readonly database' });
+  readonly.close();
+
+  t.throws(() => new Database(file, { journalMode: 'fast' as 'wal' }), {
+    message: 'Invalid journalMode "fast", expected one of delete, truncate, persist, memory, wal, off',
+  });
+  t.throws(() => new Database(file, { synchronous: 'always' as 'full' }), {
+    message: 'Invalid synchronous "always", expected one of off, normal, full, extra',
+  });
+  fs.rmSync(dir, { recursive: true, force: true });
+});
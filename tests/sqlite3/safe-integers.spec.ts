@@ -0,0 +1,54 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+// A snowflake above Number.MAX_SAFE_INTEGER
+const id = 604227193651986443n;
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, score REAL)');
+  db.prepare('INSERT INTO users VALUES (?, ?, ?)').run([id, 'Amniel', 9.5]);
+  return db;
+}
+
+test('disabled', (t) => {
+  const db = setup();
+  const row = db.prepare('SELECT id FROM users').get() as { id: number };
+  t.is(typeof row.id, 'number');
+  t.not(BigInt(row.id), id);
+  db.close();
+});
+
+test('statement', (t) => {
+  const db = setup();
+  const stmt = db.prepare('SELECT * FROM users WHERE id = ?').safeIntegers();
+  t.deepEqual(stmt.get([id]), { id, name: 'Amniel', score: 9.5 });
+  t.deepEqual(stmt.raw().all([id]), [[id, 'Amniel', 9.5]]);
+  t.deepEqual([...stmt.pluck().iterate([id])], [id]);
+
+  t.deepEqual(stmt.safeIntegers(false).get([id]), Number(id));
+  db.close();
+});
+
+test('database', (t) => {
+  const db = setup();
+  const before = db.prepare('SELECT id FROM users').pluck();
+  t.is(db.safeIntegers(), db);
+
+  const after = db.prepare('SELECT id FROM users').pluck();
+  t.is(typeof before.get(), 'number');
+  t.is(after.get(), id);
+
+  db.safeIntegers(false);
+  t.is(typeof db.prepare('SELECT id FROM users').pluck().get(), 'number');
+  db.close();
+});
+
+test('lastInsertRowid', (t) => {
+  const db = setup();
+  const insert = db.prepare('INSERT INTO users (name) VALUES (?)');
+  t.is(insert.safeIntegers().run(['Rust']).lastInsertRowid, id + 1n);
+  t.is(insert.safeIntegers(false).run(['Node']).lastInsertRowid, Number(id + 2n));
+  db.close();
+});
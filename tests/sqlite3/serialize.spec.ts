@@ -0,0 +1,59 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec(`
+    CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+    INSERT INTO users (name) VALUES ('Amniel'), ('Rust');
+  `);
+  return db;
+}
+
+test('serialize', (t) => {
+  const db = setup();
+  const buffer = db.serialize();
+
+  t.true(Buffer.isBuffer(buffer));
+  t.is(buffer.subarray(0, 15).toString(), 'SQLite format 3');
+  t.deepEqual(db.serialize('main'), buffer);
+
+  db.exec("ATTACH ':memory:' AS other");
+  t.true(Buffer.isBuffer(db.serialize('other')));
+  t.throws(() => db.serialize('missing'));
+  db.close();
+});
+
+test('deserialize', (t) => {
+  const db = setup();
+  const copy = Database.deserialize(db.serialize());
+
+  t.is(copy.name, ':memory:');
+  t.deepEqual(copy.prepare('SELECT name FROM users').pluck().all(), ['Amniel', 'Rust']);
+  copy.exec("INSERT INTO users (name) VALUES ('Node')");
+  t.is(copy.prepare('SELECT count(*) FROM users').pluck().get(), 3);
+  t.is(db.prepare('SELECT count(*) FROM users').pluck().get(), 2);
+
+  const empty = Database.deserialize(Buffer.alloc(0));
+  t.deepEqual(empty.prepare('SELECT name FROM sqlite_schema').all(), []);
+
+  copy.close();
+  empty.close();
+  db.close();
+});
+
+test('readonly', (t) => {
+  const db = setup();
+  const copy = Database.deserialize(db.serialize(), { readonly: true });
+
+  t.is(copy.prepare('SELECT count(*) FROM users').pluck().get(), 2);
+  t.throws(() => copy.exec("INSERT INTO users (name) VALUES ('Node')"), { message: /readonly/ });
+  copy.close();
+  db.close();
+});
+
+test('invalid', (t) => {
+  t.throws(() => Database.deserialize(Buffer.from('not a database')), { message: /not a database/ });
+  t.throws(() => Database.deserialize('buffer' as any));
+});
@@ -0,0 +1,109 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+const SCHEMA = 'CREATE TABLE todos (id INTEGER PRIMARY KEY, title TEXT, done INTEGER); CREATE TABLE notes (body TEXT)';
+
+function setup() {
+  const local = new Database(':memory:');
+  const remote = new Database(':memory:');
+  local.exec(SCHEMA);
+  remote.exec(SCHEMA);
+  local.exec("INSERT INTO todos VALUES (1, 'Write docs', 0), (2, 'Fix bug', 0), (3, 'Release', 0)");
+  remote.exec("INSERT INTO todos VALUES (1, 'Write docs', 0), (2, 'Fix bug', 0), (3, 'Release', 0)");
+  return { local, remote };
+}
+
+test('changeset', (t) => {
+  const { local, remote } = setup();
+  const session = local.createSession();
+  t.true(session.isEmpty);
+
+  local.exec("UPDATE todos SET done = 1 WHERE id = 1; DELETE FROM todos WHERE id = 2; INSERT INTO todos VALUES (4, 'Blog', 0)");
+  local.exec("INSERT INTO notes VALUES ('not recorded without a primary key')");
+  t.false(session.isEmpty);
+
+  const changeset = session.changeset();
+  t.true(Buffer.isBuffer(changeset));
+  remote.applyChangeset(changeset);
+  t.deepEqual(remote.prepare('SELECT * FROM todos ORDER BY id').all(), local.prepare('SELECT * FROM todos ORDER BY id').all());
+  t.is(remote.prepare('SELECT count(*) FROM notes').pluck().get(), 0);
+
+  t.true(session.patchset().length < changeset.length);
+  t.throws(() => local.close(), { message: 'This database connection is busy executing a query' });
+  session.close();
+  session.close();
+  t.throws(() => session.changeset(), { message: 'The session is closed' });
+  local.close();
+  remote.close();
+});
+
+test('createSession with tables', (t) => {
+  const { local, remote } = setup();
+  const session = local.createSession(['notes']);
+  local.exec("UPDATE todos SET done = 1; INSERT INTO notes VALUES ('hello')");
+  t.true(session.isEmpty);
+
+  session.enable(false);
+  local.exec('DELETE FROM todos');
+  session.enable();
+  const all = local.createSession();
+  local.exec("INSERT INTO todos VALUES (5, 'Review', 1)");
+  remote.applyChangeset(all.changeset());
+  t.is(remote.prepare('SELECT title FROM todos WHERE id = 5').pluck().get(), 'Review');
+
+  session.close();
+  all.close();
+  local.close();
+  remote.close();
+});
+
+test('applyChangeset conflicts', (t) => {
+  const { local, remote } = setup();
+  const session = local.createSession(['todos']);
+  local.exec("UPDATE todos SET done = 1 WHERE id = 1; INSERT INTO todos VALUES (4, 'Blog', 0)");
+  const changeset = session.changeset();
+  session.close();
+
+  remote.exec("UPDATE todos SET done = 2 WHERE id = 1; INSERT INTO todos VALUES (4, 'Podcast', 1)");
+  t.throws(() => remote.applyChangeset(changeset), { code: 'SQLITE_ABORT' });
+  t.is(remote.prepare('SELECT done FROM todos WHERE id = 1').pluck().get(), 2);
+
+  const conflicts: unknown[] = [];
+  remote.applyChangeset(changeset, {
+    onConflict: (conflict) => {
+      conflicts.push(conflict);
+      return conflict.reason === 'data' ? 'replace' : 'omit';
+    },
+  });
+  t.deepEqual(conflicts, [
+    {
+      reason: 'data',
+      table: 'todos',
+      op: 'update',
+      old: [1, null, 0],
+      new: [null, null, 1],
+      conflicting: [1, 'Write docs', 2],
+    },
+    { reason: 'conflict', table: 'todos', op: 'insert', new: [4, 'Blog', 0], conflicting: [4, 'Podcast', 1] },
+  ]);
+  t.deepEqual(remote.prepare('SELECT * FROM todos WHERE id IN (1, 4) ORDER BY id').all(), [
+    { id: 1, title: 'Write docs', done: 1 },
+    { id: 4, title: 'Podcast', done: 1 },
+  ]);
+
+  remote.exec('UPDATE todos SET done = 0');
+  const error = new Error('Conflict');
+  const onConflict = () => {
+    throw error;
+  };
+  t.is(t.throws(() => remote.applyChangeset(changeset, { onConflict })), error);
+  t.throws(() => remote.applyChangeset(changeset, { onConflict: () => 'merge' as 'omit' }), {
+    message: 'Unknown conflict resolution "merge", expected omit, replace or abort',
+  });
+  t.is(remote.prepare('SELECT count(*) FROM todos WHERE done = 1').pluck().get(), 0);
+  t.throws(() => remote.applyChangeset(Buffer.from('not a changeset')));
+
+  local.close();
+  remote.close();
+});
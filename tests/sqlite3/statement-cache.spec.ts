@@ -0,0 +1,69 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT); INSERT INTO users (name) VALUES ('Amniel'), ('Rust')");
+  return db;
+}
+
+test('statementCacheStats', (t) => {
+  const db = setup();
+  t.deepEqual(db.statementCacheStats(), { hits: 0, misses: 0, evictions: 0, size: 0, capacity: 16 });
+
+  // Running a statement the first time uses the one compiled when preparing it.
+  const select = db.prepare('SELECT name FROM users WHERE id = ?');
+  select.get([1]);
+  t.deepEqual(db.statementCacheStats(), { hits: 0, misses: 1, evictions: 0, size: 1, capacity: 16 });
+
+  select.get([2]);
+  db.prepare('  SELECT name FROM users WHERE id = ?  ').pluck().get([1]);
+  t.deepEqual(db.statementCacheStats(), { hits: 2, misses: 1, evictions: 0, size: 1, capacity: 16 });
+
+  for (const name of select.iterate([1])) {
+    t.truthy(name);
+    // The iterator holds its statement until it finishes, so the cache compiles another one.
+    t.is(db.prepare('SELECT name FROM users WHERE id = ?').pluck().get([2]), 'Rust');
+    t.like(db.statementCacheStats(), { hits: 3, misses: 2, evictions: 0, size: 1 });
+  }
+  // The cache keeps one statement for each SQL text.
+  t.like(db.statementCacheStats(), { hits: 3, misses: 2, evictions: 1, size: 1 });
+  db.close();
+});
+
+test('setStatementCacheCapacity', (t) => {
+  const db = setup();
+  t.is(db.setStatementCacheCapacity(2), db);
+
+  const statements = [1, 2, 3].map((n) => db.prepare(`SELECT ${n}`));
+  t.deepEqual(db.statementCacheStats(), { hits: 0, misses: 3, evictions: 1, size: 2, capacity: 2 });
+  statements[0].get();
+  t.like(db.statementCacheStats(), { misses: 4, evictions: 2, size: 2 });
+  statements[2].get();
+  statements[2].get();
+  t.like(db.statementCacheStats(), { hits: 1, evictions: 2 });
+
+  db.setStatementCacheCapacity(1);
+  t.like(db.statementCacheStats(), { evictions: 3, size: 1 });
+
+  db.setStatementCacheCapacity(0);
+  statements[2].get();
+  statements[2].get();
+  t.deepEqual(db.statementCacheStats(), { hits: 1, misses: 6, evictions: 6, size: 0, capacity: 0 });
+  db.close();
+  t.throws(() => db.statementCacheStats(), { message: 'The database connection is not open' });
+});
+
+test('flushed statements are not evictions', (t) => {
+  const db = setup();
+  db.prepare('SELECT 1').get();
+  db.prepare('SELECT 2').get();
+  // Defining a table flushes the cache.
+  db.table('numbers', { columns: ['value'], *rows() { yield [1]; } });
+  t.deepEqual(db.statementCacheStats(), { hits: 0, misses: 2, evictions: 0, size: 0, capacity: 16 });
+
+  db.prepare('SELECT 1').get();
+  t.like(db.statementCacheStats(), { hits: 0, misses: 3, evictions: 0, size: 1 });
+  db.close();
+});
@@ -0,0 +1,107 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, score REAL)');
+  const insert = db.prepare('INSERT INTO users (name, score) VALUES (?, ?)');
+  insert.run(['Amniel', 9.5]);
+  insert.run(['Rust', 7]);
+  insert.run(['Node', null]);
+  return db;
+}
+
+test('prepare', (t) => {
+  const db = setup();
+  const stmt = db.prepare('SELECT * FROM users');
+  t.is(stmt.source, 'SELECT * FROM users');
+  t.true(stmt.reader);
+  t.true(stmt.readonly);
+
+  const insert = db.prepare('INSERT INTO users (name) VALUES (?)');
+  t.false(insert.reader);
+  t.false(insert.readonly);
+
+  t.throws(() => db.prepare('SELECT * FROM missing'), { message: /no such table/ });
+  db.close();
+});
+
+test('run', (t) => {
+  const db = setup();
+  const info = db.prepare('INSERT INTO users (name) VALUES (?)').run(['Amniel']);
+  t.deepEqual(info, { changes: 1, lastInsertRowid: 4 });
+
+  t.is(db.prepare('UPDATE users SET score = 0').run().changes, 4);
+  t.is(db.prepare('DELETE FROM users WHERE id = ? RETURNING id').run([1]).changes, 1);
+  db.close();
+});
+
+test('get', (t) => {
+  const db = setup();
+  const stmt = db.prepare('SELECT * FROM users WHERE id = ?');
+  t.deepEqual(stmt.get([1]), { id: 1, name: 'Amniel', score: 9.5 });
+  t.is(stmt.get([99]), null);
+
+  t.throws(() => db.prepare('DELETE FROM users').get(), { message: /does not return data/ });
+  db.close();
+});
+
+test('all', (t) => {
+  const db = setup();
+  const rows = db.prepare('SELECT name, score FROM users ORDER BY id').all();
+  t.deepEqual(rows, [
+    { name: 'Amniel', score: 9.5 },
+    { name: 'Rust', score: 7 },
+    { name: 'Node', score: null },
+  ]);
+  t.deepEqual(db.prepare('SELECT * FROM users WHERE id > ?').all([10]), []);
+  db.close();
+});
+
+test('iterate', (t) => {
+  const db = setup();
+  const stmt = db.prepare('SELECT name FROM users ORDER BY id');
+
+  const names = [];
+  for (const row of stmt.iterate()) {
+    names.push(row.name);
+  }
+  t.deepEqual(names, ['Amniel', 'Rust', 'Node']);
+
+  for (const row of stmt.iterate()) {
+    t.is(row.name, 'Amniel');
+    t.throws(() => db.close(), { message: /busy/ });
+    break;
+  }
+
+  db.close();
+  t.false(db.isOpen);
+});
+
+test('params', (t) => {
+  const db = setup();
+  const stmt = db.prepare('SELECT ? AS a, ? AS b, ? AS c');
+  t.deepEqual(stmt.get([1, 'two', null]), { a: 1, b: 'two', c: null });
+  t.throws(() => stmt.get([1]), { message: 'Expected 3 parameters, got 1' });
+  db.close();
+});
+
+test('explain', (t) => {
+  const db = setup();
+  db.exec('CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER, title TEXT)');
+  db.exec('CREATE INDEX posts_user ON posts (user_id)');
+
+  const [search] = db.prepare('SELECT * FROM posts WHERE user_id = ?').explain();
+  t.like(search, { detail: 'SEARCH posts USING INDEX posts_user (user_id=?)', children: [] });
+
+  const plan = db.prepare('SELECT * FROM users WHERE id IN (SELECT user_id FROM posts)').explain();
+  t.deepEqual(
+    plan.map((step) => [step.detail, step.children.map((child) => child.detail)]),
+    [
+      ['SEARCH users USING INTEGER PRIMARY KEY (rowid=?)', []],
+      ['LIST SUBQUERY 1', ['SCAN posts USING COVERING INDEX posts_user']],
+    ],
+  );
+  db.close();
+});
@@ -0,0 +1,81 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+const catalogues: Record<string, Record<string, string>> = {
+  en: { 'product.apple': 'Apple', 'product.pear': 'Pear' },
+  fr: { 'product.apple': 'Pomme', 'product.pear': 'Poire' },
+};
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec("CREATE TABLE products (id INTEGER PRIMARY KEY, title_key TEXT); INSERT INTO products VALUES (1, 'product.apple'), (2, 'product.pear')");
+  db.table('translations', {
+    columns: ['key', 'text'],
+    parameters: ['locale'],
+    *rows(locale: string) {
+      yield* Object.entries(catalogues[locale] ?? {});
+    },
+  });
+  return db;
+}
+
+test('table', (t) => {
+  const db = setup();
+
+  t.deepEqual(db.prepare("SELECT p.id, t.text FROM products p JOIN translations('fr') t ON t.key = p.title_key ORDER BY p.id").all(), [
+    { id: 1, text: 'Pomme' },
+    { id: 2, text: 'Poire' },
+  ]);
+  t.deepEqual(db.prepare("SELECT text, locale FROM translations WHERE locale = ? AND key = 'product.pear'").all(['en']), [
+    { text: 'Pear', locale: 'en' },
+  ]);
+  t.is(db.prepare('SELECT count(*) FROM translations').pluck().get(), 0);
+  t.deepEqual(db.prepare('SELECT rowid FROM translations(?)').pluck().all(['en']), [1, 2]);
+  db.close();
+});
+
+test('table rows and parameters', (t) => {
+  const db = new Database(':memory:');
+  db.table('series', {
+    columns: ['value', 'square'],
+    parameters: ['start', 'stop'],
+    *rows(start = 1, stop = 3) {
+      for (let value = start; value <= stop; value++) yield { value, square: value * value };
+    },
+  });
+  t.deepEqual(db.prepare('SELECT value, square FROM series').raw().all(), [[1, 1], [2, 4], [3, 9]]);
+  t.deepEqual(db.prepare('SELECT square FROM series(2, 4)').pluck().all(), [4, 9, 16]);
+  t.deepEqual(db.prepare('SELECT value FROM series WHERE stop = 2').pluck().all(), [1, 2]);
+
+  db.table('repeat', {
+    columns: ['value'],
+    *rows(times: number) {
+      for (let i = 0; i < times; i++) yield ['hello'];
+    },
+  });
+  t.deepEqual(db.prepare('SELECT value, "$1" AS times FROM repeat(2)').raw().all(), [['hello', 2], ['hello', 2]]);
+
+  db.table('series', { columns: ['value'], rows: () => [[1n], ['two'], [null], [Buffer.from('3')]][Symbol.iterator]() });
+  t.deepEqual(db.prepare('SELECT value FROM series').pluck().all(), [1, 'two', null, Buffer.from('3')]);
+
+  db.table('broken', {
+    columns: ['value'],
+    *rows() {
+      yield [1];
+      throw new Error('Catalogue not loaded');
+    },
+  });
+  t.throws(() => db.prepare('SELECT * FROM broken').all(), { message: /Catalogue not loaded/ });
+  db.table('broken', { columns: ['value'], rows: () => [1, 2][Symbol.iterator]() });
+  t.throws(() => db.prepare('SELECT * FROM broken').all(), { message: 'Invalid table "broken": Rows must be arrays or objects' });
+  db.close();
+});
+
+test('table in async queries', async (t) => {
+  const db = setup();
+  await t.throwsAsync(db.prepare("SELECT * FROM translations('en')").allAsync(), {
+    message: 'JS functions cannot be called from async queries, use the synchronous API instead',
+  });
+  db.close();
+});
@@ -0,0 +1,47 @@
+import test from 'ava';
+
+import { Database, ProfileEvent } from '../../packages/sqlite3/lib';
+
+const tick = () => new Promise((resolve) => setImmediate(resolve));
+
+test('trace', async (t) => {
+  const db = new Database(':memory:');
+  const statements: string[] = [];
+  db.trace((sql) => statements.push(sql));
+
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+  db.prepare('INSERT INTO users (name) VALUES (?)').run(['Amniel']);
+  await db.prepare('SELECT * FROM users').allAsync();
+  await tick();
+  t.deepEqual(statements, [
+    'CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)',
+    'INSERT INTO users (name) VALUES (?)',
+    'SELECT * FROM users',
+  ]);
+
+  db.trace(null);
+  db.exec('DELETE FROM users');
+  await tick();
+  t.is(statements.length, 3);
+  db.close();
+});
+
+test('profile', async (t) => {
+  const db = new Database(':memory:');
+  const events: ProfileEvent[] = [];
+  db.profile((event) => events.push(event));
+
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+  db.prepare('WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100000) SELECT count(*) FROM n').get();
+  await tick();
+  t.is(events.length, 2);
+  t.is(events[0].sql, 'CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)');
+  t.regex(events[1].sql, /^WITH RECURSIVE/);
+  t.true(events[1].duration > 0);
+
+  db.profile(null);
+  db.exec('DELETE FROM users');
+  await tick();
+  t.is(events.length, 2);
+  db.close();
+});
@@ -0,0 +1,96 @@
+import test from 'ava';
+
+import { Database } from '../../packages/sqlite3/lib';
+
+function setup() {
+  const db = new Database(':memory:');
+  db.exec('CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT UNIQUE)');
+  const insert = db.prepare('INSERT INTO users (name) VALUES (?)');
+  const count = db.prepare('SELECT count(*) FROM users').pluck();
+  return { db, insert, count };
+}
+
+test('commit', (t) => {
+  const { db, insert, count } = setup();
+  const insertMany = db.transaction((names: string[]) => {
+    t.true(db.inTransaction);
+    for (const name of names) insert.run([name]);
+    return names.length;
+  });
+
+  t.is(insertMany(['Amniel', 'Rust', 'Node']), 3);
+  t.is(count.get(), 3);
+  t.false(db.inTransaction);
+  db.close();
+});
+
+test('rollback', (t) => {
+  const { db, insert, count } = setup();
+  const insertMany = db.transaction((names: string[]) => {
+    for (const name of names) insert.run([name]);
+  });
+
+  t.throws(() => insertMany(['Amniel', 'Rust', 'Amniel']), { message: /UNIQUE constraint failed/ });
+  t.is(count.get(), 0);
+  t.false(db.inTransaction);
+
+  const error = new TypeError('Invalid user');
+  const thrower = db.transaction(() => {
+    insert.run(['Amniel']);
+    throw error;
+  });
+  t.is(t.throws(() => thrower()), error);
+  t.is(count.get(), 0);
+  db.close();
+});
+
+test('nested', (t) => {
+  const { db, insert, count } = setup();
+  const inner = db.transaction((name: string) => insert.run([name]));
+  const outer = db.transaction(() => {
+    inner('Amniel');
+    t.throws(() => inner('Amniel'), { message: /UNIQUE constraint failed/ });
+    inner('Rust');
+  });
+
+  outer();
+  t.deepEqual(db.prepare('SELECT name FROM users ORDER BY id').pluck().all(), ['Amniel', 'Rust']);
+
+  const failing = db.transaction(() => {
+    inner('Node');
+    throw new Error('Abort');
+  });
+  t.throws(() => failing(), { message: 'Abort' });
+  t.is(count.get(), 2);
+  db.close();
+});
+
+test('variants', (t) => {
+  const { db, insert, count } = setup();
+  const insertOne = db.transaction(function (this: unknown, name: string) {
+    insert.run([name]);
+    return this;
+  });
+
+  t.is(typeof insertOne.deferred, 'function');
+  t.is(typeof insertOne.immediate, 'function');
+  t.is(typeof insertOne.exclusive, 'function');
+  t.is(typeof insertOne.immediate.exclusive, 'function');
+
+  const context = {};
+  t.is(insertOne.deferred.call(context, 'Amniel'), context);
+  insertOne.immediate('Rust');
+  insertOne.exclusive('Node');
+  t.is(count.get(), 3);
+  db.close();
+});
+
+test('promise', (t) => {
+  const { db, insert, count } = setup();
+  const insertAsync = db.transaction(async (name: string) => insert.run([name]));
+
+  t.throws(() => insertAsync('Amniel'), { message: /cannot return a promise/ });
+  t.is(count.get(), 0);
+  t.false(db.inTransaction);
+  db.close();
+});